@@ -0,0 +1,40 @@
+// std
+use std::fs;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lazy_static::lazy_static;
+
+use sop_kode::hash::*;
+
+lazy_static! {
+    static ref LONG_MESSAGE: String = fs::read_to_string("benches/long_message.txt").expect("Failed to read long message");
+    static ref LONG_MESSAGE_9501: String = fs::read_to_string("benches/text files/file_9501.txt").expect("Failed to read long message");
+}
+// The same messages as `rsa_benchmark`, so the throughput can be set against encryption
+const MESSAGE: &str = "This is a test message.";
+
+fn hash_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash");
+    for message in [MESSAGE, &LONG_MESSAGE_9501, &LONG_MESSAGE] {
+        let message = message.as_bytes();
+        group.throughput(Throughput::Bytes(message.len() as u64));
+        group.bench_with_input(BenchmarkId::new("sha1", message.len()), message, |b, message| {
+            b.iter(|| sha1(message))
+        });
+        group.bench_with_input(BenchmarkId::new("sha256", message.len()), message, |b, message| {
+            b.iter(|| sha256(message))
+        });
+        group.bench_with_input(BenchmarkId::new("sha512", message.len()), message, |b, message| {
+            b.iter(|| sha512(message))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = hash_benchmark;
+    config = Criterion::default();
+    targets = hash_bench
+}
+
+criterion_main!(hash_benchmark);
@@ -1,25 +1,33 @@
 // std
 use std::fs;
 
-use num_bigint::{BigInt, BigUint};
-use num_traits::FromPrimitive;
+use num_bigint::BigUint;
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use lazy_static::lazy_static;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
+use sop_kode::hash::HashAlg;
 use sop_kode::rsa::*;
 
 lazy_static! {
-    static ref USER_1: RSA = RSA::new(2048).expect("Failed to create RSA");
-    static ref USER_2: RSA = RSA::new(2048).expect("Failed to create RSA");
+    static ref USER_1: RSA = seeded_rsa(2048, 1);
+    static ref USER_2: RSA = seeded_rsa(2048, 2);
     static ref LONG_MESSAGE: String = fs::read_to_string("benches/long_message.txt").expect("Failed to read long message");
     static ref LONG_MESSAGE_9501: String = fs::read_to_string("benches/text files/file_9501.txt").expect("Failed to read long message");
-    static ref ENCRYPTED_LONG_MESSAGE_9501: Vec<BigUint> = USER_1.encrypt_message(LONG_MESSAGE_9501.as_str(), USER_2.public_key.clone());
-    static ref ENCRYPTED_LONG_MESSAGE: Vec<BigUint> = USER_1.encrypt_message(LONG_MESSAGE.as_str(), USER_2.public_key.clone());
-    static ref ENCRYPTED_MESSAGE: Vec<BigUint> = USER_1.encrypt_message(MESSAGE, USER_2.public_key.clone());
+    static ref ENCRYPTED_LONG_MESSAGE_9501: Ciphertext = USER_1.encrypt_message(LONG_MESSAGE_9501.as_str(), USER_2.public_key.clone()).unwrap();
+    static ref ENCRYPTED_LONG_MESSAGE: Ciphertext = USER_1.encrypt_message(LONG_MESSAGE.as_str(), USER_2.public_key.clone()).unwrap();
+    static ref ENCRYPTED_MESSAGE: Ciphertext = USER_1.encrypt_message(MESSAGE, USER_2.public_key.clone()).unwrap();
+    static ref USER_4096: RSA = seeded_rsa(4096, 3);
 }
 const MESSAGE: &str = "This is a test message.";
 
+/// Generates the same key pair on every run, so results are comparable between runs.
+fn seeded_rsa(bits: usize, seed: u64) -> RSA {
+    RSA::new_with_rng(bits, &mut ChaCha20Rng::seed_from_u64(seed)).expect("Failed to create RSA")
+}
+
 fn generate_rsa_bench(c: &mut Criterion) {
     let mut group = c.benchmark_group("generate_rsa");
     for bits in [64, 256, 512, 1024, 2048, 4096].iter() {
@@ -46,7 +54,7 @@ fn encrypt_message_bench(c: &mut Criterion) {
             messages,
             |b, &messages| {
                 b.iter(|| {
-                    USER_1.encrypt_message(&messages, USER_2.public_key.clone());
+                    USER_1.encrypt_message(messages, USER_2.public_key.clone()).unwrap();
                 })
             },
         );
@@ -63,11 +71,92 @@ fn decrypt_message_bench(c: &mut Criterion) {
             messages,
             |b, &messages| {
                 b.iter(|| {
-                    USER_2.decrypt_message(messages.to_vec());
+                    USER_2.decrypt_message(messages).unwrap();
+                })
+            },
+        );
+    }
+}
+
+fn crt_decrypt_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crt_decrypt");
+    for (bits, user) in [(2048, &*USER_2), (4096, &*USER_4096)] {
+        let ciphertext = user.public_key.encrypt(&BigUint::from_bytes_be(MESSAGE.as_bytes()), &user.public_key).unwrap();
+        let crt_key = user.private_key();
+        let slow_key = crt_key.without_crt();
+
+        group.bench_with_input(
+            criterion::BenchmarkId::new("crt", bits),
+            &ciphertext,
+            |b, ciphertext| {
+                b.iter(|| {
+                    crt_key.decrypt(ciphertext);
+                })
+            },
+        );
+        group.bench_with_input(
+            criterion::BenchmarkId::new("non_crt", bits),
+            &ciphertext,
+            |b, ciphertext| {
+                b.iter(|| {
+                    slow_key.decrypt(ciphertext);
                 })
             },
         );
     }
+    group.finish();
+}
+
+fn blinding_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("blinding");
+    for (bits, user) in [(2048, &*USER_2), (4096, &*USER_4096)] {
+        let ciphertext = user.public_key.encrypt(&BigUint::from_bytes_be(MESSAGE.as_bytes()), &user.public_key).unwrap();
+        let blinded_key = user.private_key();
+        let unblinded_key = blinded_key.without_blinding();
+        let unblinded_user = RSA::from_keys(user.public_key.clone(), unblinded_key.clone());
+
+        group.bench_with_input(criterion::BenchmarkId::new("decrypt_blinded", bits), &ciphertext, |b, ciphertext| {
+            b.iter(|| blinded_key.decrypt(ciphertext))
+        });
+        group.bench_with_input(criterion::BenchmarkId::new("decrypt_unblinded", bits), &ciphertext, |b, ciphertext| {
+            b.iter(|| unblinded_key.decrypt(ciphertext))
+        });
+        group.bench_with_input(criterion::BenchmarkId::new("sign_blinded", bits), MESSAGE, |b, message| {
+            b.iter(|| user.sign(message.as_bytes(), HashAlg::Sha256).unwrap())
+        });
+        group.bench_with_input(criterion::BenchmarkId::new("sign_unblinded", bits), MESSAGE, |b, message| {
+            b.iter(|| unblinded_user.sign(message.as_bytes(), HashAlg::Sha256).unwrap())
+        });
+        // Loading turns blinding on, so this includes checking e against the key
+        let der = user.to_pkcs1_der().unwrap();
+        group.bench_with_input(criterion::BenchmarkId::new("load_pkcs1", bits), &der, |b, der| {
+            b.iter(|| RSA::from_pkcs1_der(der).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn serial_vs_parallel_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serial_vs_parallel");
+    for (message, ciphertext) in [
+        (&*LONG_MESSAGE_9501, &*ENCRYPTED_LONG_MESSAGE_9501),
+        (&*LONG_MESSAGE, &*ENCRYPTED_LONG_MESSAGE),
+    ] {
+        let length = message.len();
+        group.bench_with_input(criterion::BenchmarkId::new("encrypt_serial", length), message, |b, message| {
+            b.iter(|| USER_2.public_key.encrypt_message(message).unwrap())
+        });
+        group.bench_with_input(criterion::BenchmarkId::new("encrypt_parallel", length), message, |b, message| {
+            b.iter(|| USER_2.public_key.par_encrypt_message(message).unwrap())
+        });
+        group.bench_with_input(criterion::BenchmarkId::new("decrypt_serial", length), ciphertext, |b, ciphertext| {
+            b.iter(|| USER_2.decrypt_message(ciphertext).unwrap())
+        });
+        group.bench_with_input(criterion::BenchmarkId::new("decrypt_parallel", length), ciphertext, |b, ciphertext| {
+            b.iter(|| USER_2.par_decrypt_message(ciphertext).unwrap())
+        });
+    }
+    group.finish();
 }
 
 criterion_group! {
@@ -75,7 +164,10 @@ criterion_group! {
     config = Criterion::default();
     targets = generate_rsa_bench,
         encrypt_message_bench,
-        decrypt_message_bench
+        decrypt_message_bench,
+        crt_decrypt_bench,
+        blinding_bench,
+        serial_vs_parallel_bench
 }
 
 criterion_main!(rsa_bench);
@@ -0,0 +1,267 @@
+use crate::Error;
+
+/// The size of an AES block in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// The AES S-box (FIPS 197 §5.1.1).
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// The inverse AES S-box (FIPS 197 §5.3.2).
+const INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+/// The round constants used by the key expansion.
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Multiplies a byte by x in GF(2^8).
+#[inline]
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// Multiplies two bytes in GF(2^8).
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+/// The Aes struct holds the expanded round keys of an AES-128, AES-192 or AES-256 key (FIPS 197).
+pub struct Aes {
+    round_keys: Vec<[u8; BLOCK_SIZE]>,
+}
+
+impl Aes {
+    /// Constructs a new Aes cipher from a 16, 24 or 32 byte key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The cipher key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Aes, Error>` - Returns the cipher, or `Error::InvalidKeyLength` if the key has another length.
+    pub fn new(key: &[u8]) -> Result<Self, Error> {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            _ => return Err(Error::InvalidKeyLength),
+        };
+        let rounds = nk + 6;
+
+        // Expand the key into 4 * (rounds + 1) words
+        let mut words: Vec<[u8; 4]> = key.chunks(4).map(|w| [w[0], w[1], w[2], w[3]]).collect();
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(|b| SBOX[b as usize]);
+                temp[0] ^= RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|b| SBOX[b as usize]);
+            }
+            let previous = words[i - nk];
+            words.push([0, 1, 2, 3].map(|j| previous[j] ^ temp[j]));
+        }
+
+        let round_keys = words
+            .chunks(4)
+            .map(|w| {
+                let mut round_key = [0u8; BLOCK_SIZE];
+                for (j, word) in w.iter().enumerate() {
+                    round_key[4 * j..4 * j + 4].copy_from_slice(word);
+                }
+                round_key
+            })
+            .collect();
+
+        Ok(Self { round_keys })
+    }
+
+    /// Encrypts a single block in place.
+    pub fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let rounds = self.round_keys.len() - 1;
+        add_round_key(block, &self.round_keys[0]);
+        for round in 1..=rounds {
+            for b in block.iter_mut() {
+                *b = SBOX[*b as usize];
+            }
+            shift_rows(block);
+            if round != rounds {
+                mix_columns(block);
+            }
+            add_round_key(block, &self.round_keys[round]);
+        }
+    }
+
+    /// Decrypts a single block in place.
+    pub fn decrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let rounds = self.round_keys.len() - 1;
+        add_round_key(block, &self.round_keys[rounds]);
+        for round in (0..rounds).rev() {
+            inv_shift_rows(block);
+            for b in block.iter_mut() {
+                *b = INV_SBOX[*b as usize];
+            }
+            add_round_key(block, &self.round_keys[round]);
+            if round != 0 {
+                inv_mix_columns(block);
+            }
+        }
+    }
+}
+
+fn add_round_key(block: &mut [u8; BLOCK_SIZE], round_key: &[u8; BLOCK_SIZE]) {
+    for (b, k) in block.iter_mut().zip(round_key) {
+        *b ^= k;
+    }
+}
+
+/// The state is stored column by column, so byte `r + 4c` is row `r` of column `c`.
+fn shift_rows(block: &mut [u8; BLOCK_SIZE]) {
+    let state = *block;
+    for r in 1..4 {
+        for c in 0..4 {
+            block[r + 4 * c] = state[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+fn inv_shift_rows(block: &mut [u8; BLOCK_SIZE]) {
+    let state = *block;
+    for r in 1..4 {
+        for c in 0..4 {
+            block[r + 4 * ((c + r) % 4)] = state[r + 4 * c];
+        }
+    }
+}
+
+fn mix_columns(block: &mut [u8; BLOCK_SIZE]) {
+    for column in block.chunks_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = xtime(a0) ^ (xtime(a1) ^ a1) ^ a2 ^ a3;
+        column[1] = a0 ^ xtime(a1) ^ (xtime(a2) ^ a2) ^ a3;
+        column[2] = a0 ^ a1 ^ xtime(a2) ^ (xtime(a3) ^ a3);
+        column[3] = (xtime(a0) ^ a0) ^ a1 ^ a2 ^ xtime(a3);
+    }
+}
+
+fn inv_mix_columns(block: &mut [u8; BLOCK_SIZE]) {
+    for column in block.chunks_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = gf_mul(a0, 14) ^ gf_mul(a1, 11) ^ gf_mul(a2, 13) ^ gf_mul(a3, 9);
+        column[1] = gf_mul(a0, 9) ^ gf_mul(a1, 14) ^ gf_mul(a2, 11) ^ gf_mul(a3, 13);
+        column[2] = gf_mul(a0, 13) ^ gf_mul(a1, 9) ^ gf_mul(a2, 14) ^ gf_mul(a3, 11);
+        column[3] = gf_mul(a0, 11) ^ gf_mul(a1, 13) ^ gf_mul(a2, 9) ^ gf_mul(a3, 14);
+    }
+}
+
+/// The `cbc_encrypt` function encrypts a message with AES in CBC mode, after padding it to a whole
+/// number of blocks with PKCS#7 padding (RFC 5652 §6.3).
+///
+/// # Arguments
+///
+/// * `key` - The 16, 24 or 32 byte cipher key.
+/// * `iv` - The initialization vector.
+/// * `plaintext` - The message to encrypt.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the ciphertext, or `Error::InvalidKeyLength` if the key has the wrong length.
+pub fn cbc_encrypt(key: &[u8], iv: &[u8; BLOCK_SIZE], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+    let aes = Aes::new(key)?;
+    let padding = BLOCK_SIZE - plaintext.len() % BLOCK_SIZE;
+    let mut padded = plaintext.to_vec();
+    padded.resize(plaintext.len() + padding, padding as u8);
+
+    let mut previous = *iv;
+    let mut ciphertext = Vec::with_capacity(padded.len());
+    for chunk in padded.chunks(BLOCK_SIZE) {
+        let mut block = [0u8; BLOCK_SIZE];
+        for (i, b) in chunk.iter().enumerate() {
+            block[i] = b ^ previous[i];
+        }
+        aes.encrypt_block(&mut block);
+        ciphertext.extend_from_slice(&block);
+        previous = block;
+    }
+
+    Ok(ciphertext)
+}
+
+/// The `cbc_decrypt` function decrypts a message encrypted with `cbc_encrypt` and removes the PKCS#7 padding.
+///
+/// # Arguments
+///
+/// * `key` - The 16, 24 or 32 byte cipher key.
+/// * `iv` - The initialization vector.
+/// * `ciphertext` - The message to decrypt.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the plaintext, or `Error::InvalidPadding` if the ciphertext is not a whole
+///   number of blocks or the padding is malformed, which usually means the key is wrong.
+pub fn cbc_decrypt(key: &[u8], iv: &[u8; BLOCK_SIZE], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+    let aes = Aes::new(key)?;
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(Error::InvalidPadding);
+    }
+
+    let mut previous = *iv;
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+    for chunk in ciphertext.chunks(BLOCK_SIZE) {
+        let mut block = [0u8; BLOCK_SIZE];
+        block.copy_from_slice(chunk);
+        aes.decrypt_block(&mut block);
+        for (i, b) in block.iter().enumerate() {
+            plaintext.push(b ^ previous[i]);
+        }
+        previous.copy_from_slice(chunk);
+    }
+
+    let padding = *plaintext.last().unwrap() as usize;
+    if padding == 0 || padding > BLOCK_SIZE || plaintext[plaintext.len() - padding..].iter().any(|&b| b as usize != padding) {
+        return Err(Error::InvalidPadding);
+    }
+    plaintext.truncate(plaintext.len() - padding);
+
+    Ok(plaintext)
+}
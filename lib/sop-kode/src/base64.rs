@@ -0,0 +1,154 @@
+use crate::Error;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// The `encode` function encodes bytes as standard Base64 with `=` padding (RFC 4648 §4).
+///
+/// # Arguments
+///
+/// * `data` - The bytes to encode.
+///
+/// # Returns
+///
+/// * `String` - Returns the Base64 encoding of `data`.
+///
+/// # Example
+///
+/// ```
+/// use sop_kode::base64;
+/// assert_eq!(base64::encode(b"Hej"), "SGVq");
+/// ```
+pub fn encode(data: &[u8]) -> String {
+    encode_with(data, ALPHABET, true)
+}
+
+/// The `encode_url` function encodes bytes as unpadded Base64url (RFC 4648 §5), as used by JWK.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to encode.
+///
+/// # Returns
+///
+/// * `String` - Returns the Base64url encoding of `data` without `=` padding.
+///
+/// # Example
+///
+/// ```
+/// use sop_kode::base64;
+/// assert_eq!(base64::encode_url(&[0xfb, 0xff]), "-_8");
+/// ```
+pub fn encode_url(data: &[u8]) -> String {
+    encode_with(data, URL_ALPHABET, false)
+}
+
+fn encode_with(data: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let triple = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(alphabet[(triple >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else if pad {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// The `decode` function decodes standard Base64 with `=` padding (RFC 4648 §4).
+///
+/// # Arguments
+///
+/// * `encoded` - The Base64 text to decode.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the decoded bytes, or `Error::InvalidBase64` if the text is
+///   not valid padded Base64.
+pub fn decode(encoded: &str) -> Result<Vec<u8>, Error> {
+    let bytes = encoded.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(Error::InvalidBase64);
+    }
+
+    decode_with(bytes, decode_symbol)
+}
+
+/// The `decode_url` function decodes unpadded Base64url (RFC 4648 §5), as used by JWK.
+///
+/// # Arguments
+///
+/// * `encoded` - The Base64url text to decode.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the decoded bytes, or `Error::InvalidBase64` if the text is
+///   not valid unpadded Base64url.
+pub fn decode_url(encoded: &str) -> Result<Vec<u8>, Error> {
+    let bytes = encoded.as_bytes();
+    // A single symbol in the last group cannot encode a whole byte
+    if bytes.len() % 4 == 1 || bytes.contains(&b'=') {
+        return Err(Error::InvalidBase64);
+    }
+
+    // Restore the padding so both variants share the strict decoder
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(4) * 4, b'=');
+    decode_with(&padded, decode_url_symbol)
+}
+
+fn decode_with(bytes: &[u8], decode_symbol: fn(u8) -> Result<u8, Error>) -> Result<Vec<u8>, Error> {
+    let mut decoded = Vec::with_capacity(bytes.len() / 4 * 3);
+
+    for (index, chunk) in bytes.chunks(4).enumerate() {
+        let is_last = index == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err(Error::InvalidBase64);
+        }
+
+        let mut triple = 0u32;
+        for &c in &chunk[..4 - padding] {
+            triple = triple << 6 | decode_symbol(c)? as u32;
+        }
+        triple <<= 6 * padding;
+
+        let triple_bytes = [(triple >> 16) as u8, (triple >> 8) as u8, triple as u8];
+        // The bits dropped by the padding must be zero for the encoding to be canonical
+        if triple_bytes[3 - padding..].iter().any(|&b| b != 0) {
+            return Err(Error::InvalidBase64);
+        }
+        decoded.extend_from_slice(&triple_bytes[..3 - padding]);
+    }
+
+    Ok(decoded)
+}
+
+/// Maps a Base64 symbol back to its 6-bit value.
+fn decode_symbol(c: u8) -> Result<u8, Error> {
+    match c {
+        b'A'..=b'Z' => Ok(c - b'A'),
+        b'a'..=b'z' => Ok(c - b'a' + 26),
+        b'0'..=b'9' => Ok(c - b'0' + 52),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(Error::InvalidBase64),
+    }
+}
+
+/// Maps a Base64url symbol back to its 6-bit value.
+fn decode_url_symbol(c: u8) -> Result<u8, Error> {
+    match c {
+        b'-' => Ok(62),
+        b'_' => Ok(63),
+        b'+' | b'/' => Err(Error::InvalidBase64),
+        _ => decode_symbol(c),
+    }
+}
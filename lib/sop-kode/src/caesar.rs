@@ -1,3 +1,5 @@
+use crate::Error;
+
 /// Performs a Caesar shift on a given string.
 ///
 /// # Arguments
@@ -8,7 +10,8 @@
 ///
 /// # Returns
 ///
-/// * A String that represents the encrypted string.
+/// * A `Result<String, Error>` that holds the encrypted string, or `Error::UnknownSymbol` if a
+///   character is not part of the alphabet.
 ///
 /// # Example
 ///
@@ -19,16 +22,19 @@
 /// let alphabet = vec!["A", "B", "C", "D", "E", "F", "G", "H", "I", "J",
 ///                     "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T",
 ///                     "U", "V", "W", "X", "Y", "Z", "Æ", "Ø", "Å"];
-/// let encrypted = caesar_shift(plaintext, 3, alphabet);
+/// let encrypted = caesar_shift(plaintext, 3, alphabet).unwrap();
 /// println!("Encrypted: {}", encrypted); // PHWWH
 /// ```
-pub fn caesar_shift(m: &str, k: u8, n: Vec<&str>) -> String {
+pub fn caesar_shift(m: &str, k: u8, n: Vec<&str>) -> Result<String, Error> {
     m.chars()
         .filter(|&c| c != ' ')
         .map(|i| {
-            let pos = n.iter().position(|&r| r == i.to_string()).unwrap();
+            let pos = n
+                .iter()
+                .position(|&r| r == i.to_string())
+                .ok_or(Error::UnknownSymbol(i))?;
             let new_pos = (pos + k as usize) % n.len();
-            n[new_pos].to_string()
+            Ok(n[new_pos].to_string())
         })
         .collect()
 }
@@ -0,0 +1,235 @@
+use num_bigint::BigUint;
+use crate::Error;
+
+/// The DER tag of an INTEGER.
+pub const TAG_INTEGER: u8 = 0x02;
+/// The DER tag of a BIT STRING.
+pub const TAG_BIT_STRING: u8 = 0x03;
+/// The DER tag of an OCTET STRING.
+pub const TAG_OCTET_STRING: u8 = 0x04;
+/// The DER tag of NULL.
+pub const TAG_NULL: u8 = 0x05;
+/// The DER tag of an OBJECT IDENTIFIER.
+pub const TAG_OBJECT_IDENTIFIER: u8 = 0x06;
+/// The DER tag of a SEQUENCE.
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+/// The `encode_tlv` function encodes a single DER tag-length-value element.
+///
+/// # Arguments
+///
+/// * `tag` - The tag of the element.
+/// * `content` - The encoded content of the element.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded element.
+pub fn encode_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+
+    if content.len() < 0x80 {
+        encoded.push(content.len() as u8);
+    } else {
+        // Long form: the number of length bytes followed by the length in big-endian
+        let length_bytes: Vec<u8> = content
+            .len()
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|&b| b == 0)
+            .collect();
+        encoded.push(0x80 | length_bytes.len() as u8);
+        encoded.extend_from_slice(&length_bytes);
+    }
+
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+/// The `encode_integer` function encodes a non-negative integer as a DER INTEGER.
+///
+/// # Arguments
+///
+/// * `value` - The integer to encode.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded INTEGER.
+pub fn encode_integer(value: &BigUint) -> Vec<u8> {
+    let mut content = value.to_bytes_be();
+    // A leading one bit would make the two's complement value negative
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    encode_tlv(TAG_INTEGER, &content)
+}
+
+/// The `encode_sequence` function encodes already encoded elements as a DER SEQUENCE.
+///
+/// # Arguments
+///
+/// * `elements` - The encoded elements of the sequence.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded SEQUENCE.
+pub fn encode_sequence(elements: &[Vec<u8>]) -> Vec<u8> {
+    encode_tlv(TAG_SEQUENCE, &elements.concat())
+}
+
+/// The `encode_null` function encodes a DER NULL.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded NULL.
+pub fn encode_null() -> Vec<u8> {
+    encode_tlv(TAG_NULL, &[])
+}
+
+/// The `encode_oid` function encodes the content bytes of an object identifier as a DER OBJECT IDENTIFIER.
+///
+/// # Arguments
+///
+/// * `oid` - The encoded arcs of the object identifier, without tag and length.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded OBJECT IDENTIFIER.
+pub fn encode_oid(oid: &[u8]) -> Vec<u8> {
+    encode_tlv(TAG_OBJECT_IDENTIFIER, oid)
+}
+
+/// The `encode_octet_string` function encodes bytes as a DER OCTET STRING.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded OCTET STRING.
+pub fn encode_octet_string(bytes: &[u8]) -> Vec<u8> {
+    encode_tlv(TAG_OCTET_STRING, bytes)
+}
+
+/// The `encode_bit_string` function encodes whole bytes as a DER BIT STRING with no unused bits.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the encoded BIT STRING.
+pub fn encode_bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = vec![0];
+    content.extend_from_slice(bytes);
+    encode_tlv(TAG_BIT_STRING, &content)
+}
+
+/// The DerReader struct reads DER elements one after another from a byte slice.
+pub struct DerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    /// Constructs a new DerReader over the given bytes.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns `true` if every element has been read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the tag of the next element without reading it.
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Checks that every element has been read.
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidDer("trailing data"))
+        }
+    }
+
+    /// Reads the next element, checks that it has the expected tag and returns its content.
+    pub fn read_tlv(&mut self, tag: u8) -> Result<&'a [u8], Error> {
+        let (&actual_tag, rest) = self.data.split_first().ok_or(Error::InvalidDer("unexpected end of data"))?;
+        if actual_tag != tag {
+            return Err(Error::InvalidDer("unexpected tag"));
+        }
+
+        let (&first, mut rest) = rest.split_first().ok_or(Error::InvalidDer("missing length"))?;
+        let length = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > std::mem::size_of::<usize>() || count > rest.len() {
+                return Err(Error::InvalidDer("invalid length"));
+            }
+            let (length_bytes, remaining) = rest.split_at(count);
+            rest = remaining;
+            if length_bytes[0] == 0 {
+                return Err(Error::InvalidDer("non-minimal length"));
+            }
+            let length = length_bytes.iter().fold(0usize, |acc, &b| acc << 8 | b as usize);
+            if length < 0x80 {
+                return Err(Error::InvalidDer("non-minimal length"));
+            }
+            length
+        };
+
+        if length > rest.len() {
+            return Err(Error::InvalidDer("length exceeds data"));
+        }
+        let (content, remaining) = rest.split_at(length);
+        self.data = remaining;
+        Ok(content)
+    }
+
+    /// Reads a SEQUENCE and returns a reader over its elements.
+    pub fn read_sequence(&mut self) -> Result<DerReader<'a>, Error> {
+        Ok(DerReader::new(self.read_tlv(TAG_SEQUENCE)?))
+    }
+
+    /// Reads a non-negative INTEGER.
+    pub fn read_integer(&mut self) -> Result<BigUint, Error> {
+        let content = self.read_tlv(TAG_INTEGER)?;
+        match content {
+            [] => Err(Error::InvalidDer("empty integer")),
+            [first, ..] if first & 0x80 != 0 => Err(Error::InvalidDer("negative integer")),
+            [0, second, ..] if second & 0x80 == 0 => Err(Error::InvalidDer("non-minimal integer")),
+            _ => Ok(BigUint::from_bytes_be(content)),
+        }
+    }
+
+    /// Reads a NULL.
+    pub fn read_null(&mut self) -> Result<(), Error> {
+        if !self.read_tlv(TAG_NULL)?.is_empty() {
+            return Err(Error::InvalidDer("non-empty null"));
+        }
+        Ok(())
+    }
+
+    /// Reads an OBJECT IDENTIFIER and returns its encoded arcs.
+    pub fn read_oid(&mut self) -> Result<&'a [u8], Error> {
+        self.read_tlv(TAG_OBJECT_IDENTIFIER)
+    }
+
+    /// Reads an OCTET STRING.
+    pub fn read_octet_string(&mut self) -> Result<&'a [u8], Error> {
+        self.read_tlv(TAG_OCTET_STRING)
+    }
+
+    /// Reads a BIT STRING made of whole bytes.
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], Error> {
+        match self.read_tlv(TAG_BIT_STRING)? {
+            [0, bytes @ ..] => Ok(bytes),
+            _ => Err(Error::InvalidDer("bit string with unused bits")),
+        }
+    }
+}
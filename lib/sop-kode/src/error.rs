@@ -0,0 +1,122 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+
+/// The Error enum describes everything that can go wrong in `sop_kode`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The two primes of a key are equal.
+    PrimeCollision,
+    /// A prime of a key is smaller than 2, or the two primes share a factor.
+    InvalidPrimeFactors,
+    /// The public exponent has no inverse modulo the totient, so no private exponent exists.
+    NonInvertibleExponent,
+    /// The message, or a chunk of it, is not smaller than the modulus.
+    MessageTooLong,
+    /// The padding of a decrypted message is malformed.
+    InvalidPadding,
+    /// The decrypted message is not valid UTF-8, which usually means the wrong key was used.
+    InvalidUtf8,
+    /// The character is not part of the alphabet.
+    UnknownSymbol(char),
+    /// A message cannot be split into chunks of zero bytes.
+    InvalidChunkSize,
+    /// The requested modulus size is smaller than the minimum.
+    BitsTooSmall { bits: usize, min: usize },
+    /// The requested modulus size is odd, so it cannot be split evenly between `p` and `q`.
+    OddBitSize(usize),
+    /// The public exponent must be odd and greater than 1.
+    InvalidPublicExponent(BigUint),
+    /// At least one Miller-Rabin round is needed to test the primes.
+    ZeroRounds,
+    /// A freshly generated key did not decrypt a test message it had encrypted.
+    PairwiseConsistency,
+    /// The public exponent given for blinding does not undo the private exponent of the key.
+    ExponentMismatch,
+    /// The private key does not hold the prime factors needed for this operation.
+    MissingPrimeFactors,
+    /// The text is not valid Base64.
+    InvalidBase64,
+    /// The PEM armor is malformed or carries an unexpected label.
+    InvalidPem,
+    /// The DER encoding is malformed.
+    InvalidDer(&'static str),
+    /// Reading or writing a file failed.
+    Io(std::io::ErrorKind),
+    /// A symmetric cipher key has the wrong length.
+    InvalidKeyLength,
+    /// The encrypted private key could not be decrypted with the given password.
+    WrongPassword,
+    /// The private key is encrypted, so it has to be loaded with a password.
+    PasswordRequired,
+    /// The file uses an algorithm that is not supported.
+    UnsupportedAlgorithm,
+    /// The OpenSSH public key is malformed or not an RSA key.
+    InvalidOpenSsh,
+    /// The recipient's modulus is too small to hold a chunk under the padding scheme.
+    KeyTooSmall,
+    /// The decrypted length prefix does not match the message, which usually means the wrong key was used.
+    InvalidFraming,
+    /// The ciphertext container is malformed; the reason names the failed check.
+    InvalidCiphertext(&'static str),
+    /// The ciphertext was encrypted to a different key.
+    WrongKey,
+    /// The ciphertext was bound to an OAEP label but none was given to decrypt it, or the other way round.
+    LabelMismatch,
+    /// The ASCII armor is malformed; the reason names the failed check.
+    InvalidArmor(&'static str),
+    /// The armored data does not match its CRC-24 checksum, so it was damaged in transit.
+    ArmorChecksumMismatch { expected: u32, actual: u32 },
+    /// The JSON Web Key is malformed or not an RSA key; the reason names the offending member.
+    InvalidJwk(&'static str),
+    /// The signature does not belong to the message and key.
+    InvalidSignature,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::PrimeCollision => write!(f, "the primes p and q are equal"),
+            Error::InvalidPrimeFactors => write!(f, "the primes p and q must be at least 2 and coprime"),
+            Error::NonInvertibleExponent => write!(f, "public exponent is not coprime to phi(n)"),
+            Error::MessageTooLong => write!(f, "message is too long for the key"),
+            Error::InvalidPadding => write!(f, "invalid padding"),
+            Error::InvalidUtf8 => write!(f, "decrypted message is not valid UTF-8"),
+            Error::UnknownSymbol(c) => write!(f, "symbol {:?} is not in the alphabet", c),
+            Error::InvalidChunkSize => write!(f, "chunk size must be greater than zero"),
+            Error::BitsTooSmall { bits, min } => {
+                write!(f, "key size of {} bits is too small, the minimum is {} bits", bits, min)
+            }
+            Error::OddBitSize(bits) => write!(f, "key size of {} bits must be even", bits),
+            Error::InvalidPublicExponent(e) => {
+                write!(f, "public exponent {} must be odd and greater than 1", e)
+            }
+            Error::ZeroRounds => write!(f, "at least one Miller-Rabin round is required"),
+            Error::PairwiseConsistency => write!(f, "pairwise encrypt/decrypt consistency test failed"),
+            Error::ExponentMismatch => write!(f, "public exponent does not match the private exponent"),
+            Error::MissingPrimeFactors => write!(f, "private key does not hold the prime factors of n"),
+            Error::InvalidBase64 => write!(f, "invalid Base64"),
+            Error::InvalidPem => write!(f, "invalid PEM armor"),
+            Error::InvalidDer(reason) => write!(f, "invalid DER: {}", reason),
+            Error::Io(kind) => write!(f, "I/O error: {}", kind),
+            Error::InvalidKeyLength => write!(f, "invalid cipher key length"),
+            Error::WrongPassword => write!(f, "wrong password"),
+            Error::PasswordRequired => write!(f, "private key is encrypted and needs a password"),
+            Error::UnsupportedAlgorithm => write!(f, "unsupported algorithm"),
+            Error::InvalidOpenSsh => write!(f, "invalid OpenSSH public key"),
+            Error::KeyTooSmall => write!(f, "key is too small for the padding scheme"),
+            Error::InvalidFraming => write!(f, "decrypted message framing is invalid"),
+            Error::InvalidCiphertext(reason) => write!(f, "invalid ciphertext: {}", reason),
+            Error::WrongKey => write!(f, "ciphertext was encrypted to a different key"),
+            Error::LabelMismatch => write!(f, "ciphertext label does not match the label given to decrypt"),
+            Error::InvalidArmor(reason) => write!(f, "invalid armor: {}", reason),
+            Error::ArmorChecksumMismatch { expected, actual } => {
+                write!(f, "armor checksum mismatch: expected {:06x}, computed {:06x}", expected, actual)
+            }
+            Error::InvalidJwk(reason) => write!(f, "invalid JWK: {}", reason),
+            Error::InvalidSignature => write!(f, "signature verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,30 @@
+use super::sha256::{Sha256, BLOCK_SIZE, DIGEST_SIZE};
+
+/// The `hmac_sha256` function computes HMAC-SHA256 of a message (RFC 2104).
+///
+/// # Arguments
+///
+/// * `key` - The secret key.
+/// * `message` - The message to authenticate.
+///
+/// # Returns
+///
+/// * `[u8; 32]` - Returns the authentication tag.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; DIGEST_SIZE] {
+    // Keys longer than a block are hashed first, shorter keys are padded with zeros
+    let mut block_key = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block_key[..DIGEST_SIZE].copy_from_slice(&super::sha256(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(&block_key.map(|b| b ^ 0x36));
+    inner.update(message);
+
+    let mut outer = Sha256::new();
+    outer.update(&block_key.map(|b| b ^ 0x5c));
+    outer.update(&inner.finalize());
+    outer.finalize()
+}
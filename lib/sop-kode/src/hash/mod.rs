@@ -0,0 +1,110 @@
+pub mod sha1;
+pub mod sha256;
+pub mod sha512;
+pub mod hmac;
+pub mod pbkdf2;
+
+pub use sha1::{sha1, Sha1};
+pub use sha256::{sha256, Sha256};
+pub use sha512::{sha512, Sha512};
+pub use hmac::hmac_sha256;
+pub use pbkdf2::pbkdf2_hmac_sha256;
+
+/// The BlockBuffer struct collects message bytes into the fixed-size blocks of a Merkle-Damgård
+/// hash and applies its padding, so each hash only supplies its compression function.
+#[derive(Clone)]
+pub(crate) struct BlockBuffer<const BLOCK_SIZE: usize> {
+    buffer: [u8; BLOCK_SIZE],
+    buffer_len: usize,
+    length: u128,
+}
+
+impl<const BLOCK_SIZE: usize> BlockBuffer<BLOCK_SIZE> {
+    /// Constructs a new, empty BlockBuffer.
+    pub(crate) fn new() -> Self {
+        Self { buffer: [0; BLOCK_SIZE], buffer_len: 0, length: 0 }
+    }
+
+    /// The `update` function feeds more of the message in and compresses every block it completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The next part of the message.
+    /// * `compress` - The compression function, called once per full block.
+    pub(crate) fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; BLOCK_SIZE])) {
+        self.length += data.len() as u128;
+
+        if self.buffer_len > 0 {
+            let take = (BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+            if self.buffer_len < BLOCK_SIZE {
+                return;
+            }
+            compress(&self.buffer);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_SIZE);
+        for block in blocks.by_ref() {
+            compress(block.try_into().unwrap());
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffer_len = rest.len();
+    }
+
+    /// The `finalize` function pads the message (FIPS 180-4 §5.1) and compresses the last blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `length_size` - The width in bytes of the big-endian bit length at the end, 8 or 16.
+    /// * `compress` - The compression function, called once per full block.
+    pub(crate) fn finalize(mut self, length_size: usize, mut compress: impl FnMut(&[u8; BLOCK_SIZE])) {
+        let bit_length = self.length.wrapping_mul(8).to_be_bytes();
+
+        // Append a single one bit, then zeros until `length_size` bytes are left for the length
+        let mut padding = vec![0x80];
+        let padded_len = (self.buffer_len + 1 + length_size).next_multiple_of(BLOCK_SIZE);
+        padding.resize(padded_len - self.buffer_len - length_size, 0);
+        padding.extend_from_slice(&bit_length[bit_length.len() - length_size..]);
+        self.update(&padding, &mut compress);
+    }
+}
+
+/// The hash functions the crate implements, for choosing one at runtime, for example in a signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlg {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl HashAlg {
+    /// The `digest` function hashes a message with this function.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The message to hash.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - Returns the digest, `digest_size` bytes long.
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlg::Sha1 => sha1(data).to_vec(),
+            HashAlg::Sha256 => sha256(data).to_vec(),
+            HashAlg::Sha512 => sha512(data).to_vec(),
+        }
+    }
+
+    /// Returns the size of a digest in bytes.
+    pub fn digest_size(self) -> usize {
+        match self {
+            HashAlg::Sha1 => sha1::DIGEST_SIZE,
+            HashAlg::Sha256 => sha256::DIGEST_SIZE,
+            HashAlg::Sha512 => sha512::DIGEST_SIZE,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use super::hmac::hmac_sha256;
+use super::sha256::DIGEST_SIZE;
+
+/// The `pbkdf2_hmac_sha256` function derives a key from a password with PBKDF2 (RFC 8018 §5.2)
+/// using HMAC-SHA256 as the pseudorandom function.
+///
+/// # Arguments
+///
+/// * `password` - The password.
+/// * `salt` - The salt.
+/// * `iterations` - The number of iterations. More iterations make guessing passwords slower.
+/// * `key_length` - The length of the derived key in bytes.
+///
+/// # Returns
+///
+/// * `Vec<u8>` - Returns the derived key.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, key_length: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(key_length.next_multiple_of(DIGEST_SIZE));
+
+    for block_index in 1..=key_length.div_ceil(DIGEST_SIZE) as u32 {
+        let mut salted = salt.to_vec();
+        salted.extend_from_slice(&block_index.to_be_bytes());
+
+        let mut u = hmac_sha256(password, &salted);
+        let mut block = u;
+        for _ in 1..iterations {
+            u = hmac_sha256(password, &u);
+            for (b, x) in block.iter_mut().zip(u) {
+                *b ^= x;
+            }
+        }
+        key.extend_from_slice(&block);
+    }
+
+    key.truncate(key_length);
+    key
+}
@@ -0,0 +1,107 @@
+use super::BlockBuffer;
+
+/// The size of a SHA-1 digest in bytes.
+pub const DIGEST_SIZE: usize = 20;
+/// The size of a SHA-1 message block in bytes.
+pub const BLOCK_SIZE: usize = 64;
+
+/// The initial hash value (FIPS 180-4 §5.3.1).
+const H0: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The Sha1 struct computes a SHA-1 digest (FIPS 180-4 §6.1) incrementally. SHA-1 is broken for
+/// collision resistance and is only here to verify old signatures and fingerprints.
+///
+/// # Example
+///
+/// ```
+/// use sop_kode::hash::Sha1;
+///
+/// let mut hasher = Sha1::new();
+/// hasher.update(b"a");
+/// hasher.update(b"bc");
+/// assert_eq!(hasher.finalize(), sop_kode::hash::sha1(b"abc"));
+/// ```
+#[derive(Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: BlockBuffer<BLOCK_SIZE>,
+}
+
+impl Sha1 {
+    /// Constructs a new Sha1 hasher.
+    pub fn new() -> Self {
+        Self { state: H0, buffer: BlockBuffer::new() }
+    }
+
+    /// Feeds more of the message into the hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.buffer.update(data, |block| Self::compress(state, block));
+    }
+
+    /// Pads the message and returns the digest.
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        let state = &mut self.state;
+        self.buffer.finalize(8, |block| Self::compress(state, block));
+
+        let mut digest = [0u8; DIGEST_SIZE];
+        for (chunk, word) in digest.chunks_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    /// Processes a single 64-byte block.
+    fn compress(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+        let mut w = [0u32; 80];
+        for (t, chunk) in block.chunks(4).enumerate() {
+            w[t] = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        for t in 16..80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        for (t, &word) in w.iter().enumerate() {
+            // The round function and constant change every 20 rounds (FIPS 180-4 §4.1.1, §4.2.1)
+            let (f, k) = match t {
+                0..=19 => ((b & c) ^ (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) ^ (b & d) ^ (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `sha1` function computes the SHA-1 digest of a message in one call.
+///
+/// # Arguments
+///
+/// * `data` - The message to hash.
+///
+/// # Returns
+///
+/// * `[u8; 20]` - Returns the digest.
+pub fn sha1(data: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    hasher.finalize()
+}
@@ -0,0 +1,123 @@
+use super::BlockBuffer;
+
+/// The size of a SHA-256 digest in bytes.
+pub const DIGEST_SIZE: usize = 32;
+/// The size of a SHA-256 message block in bytes.
+pub const BLOCK_SIZE: usize = 64;
+
+/// The initial hash value (FIPS 180-4 §5.3.3).
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants (FIPS 180-4 §4.2.2).
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The Sha256 struct computes a SHA-256 digest (FIPS 180-4 §6.2) incrementally.
+///
+/// # Example
+///
+/// ```
+/// use sop_kode::hash::Sha256;
+///
+/// let mut hasher = Sha256::new();
+/// hasher.update(b"a");
+/// hasher.update(b"bc");
+/// assert_eq!(hasher.finalize(), sop_kode::hash::sha256(b"abc"));
+/// ```
+#[derive(Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: BlockBuffer<BLOCK_SIZE>,
+}
+
+impl Sha256 {
+    /// Constructs a new Sha256 hasher.
+    pub fn new() -> Self {
+        Self { state: H0, buffer: BlockBuffer::new() }
+    }
+
+    /// Feeds more of the message into the hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.buffer.update(data, |block| Self::compress(state, block));
+    }
+
+    /// Pads the message and returns the digest.
+    pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+        let state = &mut self.state;
+        self.buffer.finalize(8, |block| Self::compress(state, block));
+
+        let mut digest = [0u8; DIGEST_SIZE];
+        for (chunk, word) in digest.chunks_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    /// Processes a single 64-byte block.
+    fn compress(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+        let mut w = [0u32; 64];
+        for (t, chunk) in block.chunks(4).enumerate() {
+            w[t] = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        for t in 16..64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16].wrapping_add(s0).wrapping_add(w[t - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for t in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[t]).wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `sha256` function computes the SHA-256 digest of a message in one call.
+///
+/// # Arguments
+///
+/// * `data` - The message to hash.
+///
+/// # Returns
+///
+/// * `[u8; 32]` - Returns the digest.
+pub fn sha256(data: &[u8]) -> [u8; DIGEST_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
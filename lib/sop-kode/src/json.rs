@@ -0,0 +1,264 @@
+/// How deeply arrays and objects may nest before the text is rejected, so hostile input cannot
+/// exhaust the stack.
+const MAX_DEPTH: usize = 32;
+
+/// The Value enum is a parsed JSON value (RFC 8259). Numbers are kept as their text, since JWK
+/// members that matter are all strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    /// The members of an object in the order they appear. Member names are unique.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the member with the given name, if this is an object that has it.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(key, _)| key == name).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of a string value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// The `parse` function parses a JSON text holding a single value.
+///
+/// # Arguments
+///
+/// * `text` - The JSON text.
+///
+/// # Returns
+///
+/// * `Option<Value>` - Returns the value, or `None` if the text is not valid JSON, nests deeper than
+///   32 levels or repeats a member name within an object.
+pub fn parse(text: &str) -> Option<Value> {
+    let mut parser = Parser { text, position: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    (parser.position == text.len()).then_some(value)
+}
+
+/// The `encode_object` function writes an object whose members are all strings, in the given order
+/// and without whitespace.
+///
+/// # Arguments
+///
+/// * `members` - The names and string values of the members.
+///
+/// # Returns
+///
+/// * `String` - Returns the JSON text.
+pub fn encode_object(members: &[(&str, String)]) -> String {
+    let members: Vec<String> = members
+        .iter()
+        .map(|(name, value)| format!("{}:{}", encode_string(name), encode_string(value)))
+        .collect();
+    format!("{{{}}}", members.join(","))
+}
+
+/// Writes a JSON string, escaping quotes, backslashes and control characters.
+fn encode_string(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len() + 2);
+    encoded.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            c if (c as u32) < 0x20 => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// A recursive descent parser over the JSON text.
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes `expected` if it comes next.
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.text[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(depth + 1),
+            b'[' => self.array(depth + 1),
+            b'"' => self.string().map(Value::String),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ if self.eat("true") => Some(Value::Bool(true)),
+            _ if self.eat("false") => Some(Value::Bool(false)),
+            _ if self.eat("null") => Some(Value::Null),
+            _ => None,
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.position += 1;
+        let mut members: Vec<(String, Value)> = Vec::new();
+        self.skip_whitespace();
+        if self.eat("}") {
+            return Some(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek()? != b'"' {
+                return None;
+            }
+            let name = self.string()?;
+            if members.iter().any(|(key, _)| *key == name) {
+                return None;
+            }
+            self.skip_whitespace();
+            if !self.eat(":") {
+                return None;
+            }
+            let value = self.value(depth)?;
+            members.push((name, value));
+            self.skip_whitespace();
+            if self.eat("}") {
+                return Some(Value::Object(members));
+            }
+            if !self.eat(",") {
+                return None;
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.position += 1;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.eat("]") {
+            return Some(Value::Array(elements));
+        }
+        loop {
+            elements.push(self.value(depth)?);
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Some(Value::Array(elements));
+            }
+            if !self.eat(",") {
+                return None;
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.position += 1;
+        let mut text = String::new();
+        loop {
+            let c = self.text[self.position..].chars().next()?;
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Some(text),
+                '\\' => {
+                    let escape = self.peek()?;
+                    self.position += 1;
+                    match escape {
+                        b'"' => text.push('"'),
+                        b'\\' => text.push('\\'),
+                        b'/' => text.push('/'),
+                        b'b' => text.push('\u{8}'),
+                        b'f' => text.push('\u{c}'),
+                        b'n' => text.push('\n'),
+                        b'r' => text.push('\r'),
+                        b't' => text.push('\t'),
+                        b'u' => text.push(self.unicode_escape()?),
+                        _ => return None,
+                    }
+                }
+                c if (c as u32) < 0x20 => return None,
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// Reads the four hex digits after `\u`, and the low half after a high surrogate.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high);
+        }
+        if !self.eat("\\u") {
+            return None;
+        }
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.position..self.position + 4)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.position += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let start = self.position;
+        self.eat("-");
+        if !self.eat("0") {
+            self.digits()?;
+        }
+        if self.eat(".") {
+            self.digits()?;
+        }
+        if self.eat("e") || self.eat("E") {
+            let _ = self.eat("+") || self.eat("-");
+            self.digits()?;
+        }
+        Some(Value::Number(self.text[start..self.position].to_string()))
+    }
+
+    /// Consumes one or more decimal digits.
+    fn digits(&mut self) -> Option<()> {
+        let start = self.position;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.position += 1;
+        }
+        (self.position > start).then_some(())
+    }
+}
@@ -1,5 +1,12 @@
+pub mod aes;
+pub mod base64;
 pub mod caesar;
+pub mod der;
+pub mod error;
+pub mod hash;
+pub mod json;
 pub mod rsa;
 
 pub use caesar::*;
+pub use error::Error;
 pub use rsa::*;
@@ -0,0 +1,380 @@
+use crate::Error;
+use crate::base64;
+use super::container::Ciphertext;
+use super::encryption::PublicKey;
+use super::keys::RSA;
+
+/// The number of Base64 characters per line in armored output.
+const LINE_LENGTH: usize = 64;
+
+/// The initial value of the OpenPGP CRC-24 (RFC 4880 §6.1).
+const CRC24_INIT: u32 = 0xb7_04ce;
+/// The generator polynomial of the OpenPGP CRC-24.
+const CRC24_POLY: u32 = 0x186_4cfb;
+
+/// The kind of object an armor block holds, which sets its `BEGIN` and `END` label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArmorKind {
+    /// A ciphertext container, see `Ciphertext`.
+    Message,
+    /// A public key as a DER SubjectPublicKeyInfo.
+    PublicKey,
+    /// A key pair as a DER PKCS#8 PrivateKeyInfo.
+    PrivateKey,
+    /// A signature.
+    Signature,
+}
+
+impl ArmorKind {
+    const ALL: [ArmorKind; 4] = [ArmorKind::Message, ArmorKind::PublicKey, ArmorKind::PrivateKey, ArmorKind::Signature];
+
+    /// The `label` function returns the text between `-----BEGIN ` and `-----` for this kind.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - Returns the label, for example `SOP-KODE MESSAGE`.
+    pub fn label(self) -> &'static str {
+        match self {
+            ArmorKind::Message => "SOP-KODE MESSAGE",
+            ArmorKind::PublicKey => "SOP-KODE PUBLIC KEY",
+            ArmorKind::PrivateKey => "SOP-KODE PRIVATE KEY",
+            ArmorKind::Signature => "SOP-KODE SIGNATURE",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        ArmorKind::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+/// The `Armor` struct is a text encoding of binary data for pasting into chat and email, in the style
+/// of OpenPGP ASCII armor (RFC 4880 §6.2):
+///
+/// ```text
+/// -----BEGIN SOP-KODE MESSAGE-----
+/// Key: value
+///
+/// Base64 data, 64 characters per line
+/// =CRC-24 checksum
+/// -----END SOP-KODE MESSAGE-----
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Armor {
+    kind: ArmorKind,
+    headers: Vec<(String, String)>,
+    data: Vec<u8>,
+}
+
+/// The `crc24` function calculates the OpenPGP CRC-24 checksum of the data (RFC 4880 §6.1).
+///
+/// # Arguments
+///
+/// * `data` - The data to checksum.
+///
+/// # Returns
+///
+/// * `u32` - Returns the 24-bit checksum.
+///
+/// # Example
+///
+/// ```
+/// use sop_kode::rsa::armor::crc24;
+/// assert_eq!(crc24(b"123456789"), 0x21cf02);
+/// ```
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0xff_ffff
+}
+
+impl Armor {
+    /// The `new` function creates an armor block without headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of object the data holds.
+    /// * `data` - The binary data to armor.
+    ///
+    /// # Returns
+    ///
+    /// * `Armor` - Returns the armor block.
+    pub fn new(kind: ArmorKind, data: Vec<u8>) -> Self {
+        Armor { kind, headers: Vec::new(), data }
+    }
+
+    /// The `header` function adds a `Key: value` header line.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The header name, which must not be empty or contain `:` or a line break.
+    /// * `value` - The header value, which must not contain a line break.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Armor, Error>` - Returns the armor block, or `Error::InvalidArmor` if the header cannot
+    ///   be written on one line.
+    pub fn header(mut self, key: &str, value: &str) -> Result<Self, Error> {
+        let breaks_line = |text: &str| text.contains(['\r', '\n']);
+        if key.is_empty() || key.contains(':') || key.trim() != key || breaks_line(key) || breaks_line(value) {
+            return Err(Error::InvalidArmor("malformed header line"));
+        }
+        self.headers.push((key.to_string(), value.trim().to_string()));
+        Ok(self)
+    }
+
+    /// Returns the kind of object the data holds.
+    pub fn kind(&self) -> ArmorKind {
+        self.kind
+    }
+
+    /// Returns the header lines in order.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Returns the value of the first header with the given name.
+    pub fn get_header(&self, key: &str) -> Option<&str> {
+        self.headers.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the binary data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The `encode` function writes the armor block.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the armored text, ending with a line break.
+    pub fn encode(&self) -> String {
+        let label = self.kind.label();
+        let mut text = format!("-----BEGIN {}-----\n", label);
+        for (key, value) in &self.headers {
+            text.push_str(&format!("{}: {}\n", key, value));
+        }
+        text.push('\n');
+
+        let encoded = base64::encode(&self.data);
+        for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+            text.push_str(std::str::from_utf8(line).unwrap());
+            text.push('\n');
+        }
+
+        let checksum = crc24(&self.data).to_be_bytes();
+        text.push_str(&format!("={}\n", base64::encode(&checksum[1..])));
+        text.push_str(&format!("-----END {}-----\n", label));
+        text
+    }
+
+    /// The `decode` function parses an armor block. Whitespace around the block and `\r\n` line endings
+    /// are accepted; anything else around the block is an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The armored text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Armor, Error>` - Returns the armor block, `Error::ArmorChecksumMismatch` if the data does
+    ///   not match its checksum, `Error::InvalidBase64` if the data is not Base64, or `Error::InvalidArmor`
+    ///   naming the structural problem.
+    pub fn decode(text: &str) -> Result<Self, Error> {
+        let mut lines = text.trim().lines().map(str::trim_end);
+
+        let label = lines
+            .next()
+            .and_then(|line| line.strip_prefix("-----BEGIN ")?.strip_suffix("-----"))
+            .ok_or(Error::InvalidArmor("missing BEGIN line"))?;
+        let kind = ArmorKind::from_label(label).ok_or(Error::InvalidArmor("unknown armor type"))?;
+
+        // Header lines come first; Base64 never contains ':', so the blank separator line is optional
+        let mut headers = Vec::new();
+        let mut line = lines.next();
+        while let Some((key, value)) = line.and_then(|line| line.split_once(':')) {
+            if key.is_empty() || key.trim() != key {
+                return Err(Error::InvalidArmor("malformed header line"));
+            }
+            headers.push((key.to_string(), value.trim().to_string()));
+            line = lines.next();
+        }
+        if line == Some("") {
+            line = lines.next();
+        }
+
+        let mut body = String::new();
+        let mut checksum = None;
+        let end = loop {
+            let current = line.ok_or(Error::InvalidArmor("missing END line"))?;
+            if current.starts_with("-----") {
+                break current;
+            }
+            if checksum.is_some() {
+                return Err(Error::InvalidArmor("data after checksum"));
+            }
+            match current.strip_prefix('=') {
+                Some(encoded) => checksum = Some(encoded),
+                None => body.push_str(current),
+            }
+            line = lines.next();
+        };
+
+        if end.strip_prefix("-----END ").and_then(|end| end.strip_suffix("-----")) != Some(label) {
+            return Err(Error::InvalidArmor("END line does not match BEGIN"));
+        }
+        if lines.next().is_some() {
+            return Err(Error::InvalidArmor("data after END line"));
+        }
+
+        let data = base64::decode(&body)?;
+        let expected = match checksum.map(base64::decode) {
+            Some(Ok(bytes)) if bytes.len() == 3 => u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]),
+            Some(_) => return Err(Error::InvalidArmor("malformed checksum")),
+            None => return Err(Error::InvalidArmor("missing checksum")),
+        };
+        let actual = crc24(&data);
+        if actual != expected {
+            return Err(Error::ArmorChecksumMismatch { expected, actual });
+        }
+
+        Ok(Armor { kind, headers, data })
+    }
+
+    /// The `decode_kind` function parses an armor block and checks that it holds the expected kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The armored text.
+    /// * `kind` - The expected kind.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Armor, Error>` - Returns the armor block, or an error as for `decode`, or
+    ///   `Error::InvalidArmor` if it holds another kind of object.
+    pub fn decode_kind(text: &str, kind: ArmorKind) -> Result<Self, Error> {
+        let armor = Self::decode(text)?;
+        if armor.kind != kind {
+            return Err(Error::InvalidArmor("unexpected armor type"));
+        }
+        Ok(armor)
+    }
+}
+
+/// Formats a fingerprint as lowercase hex for the informational headers.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl Ciphertext {
+    /// The `to_armor` function armors the container as a `SOP-KODE MESSAGE` block with a `Recipient`
+    /// header holding the key fingerprint in hex.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the armored text.
+    pub fn to_armor(&self) -> String {
+        let headers = vec![("Recipient".to_string(), hex(self.fingerprint()))];
+        Armor { kind: ArmorKind::Message, headers, data: self.to_bytes() }.encode()
+    }
+
+    /// The `from_armor` function parses a `SOP-KODE MESSAGE` block. Headers are informational only.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The armored text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the container, or an error if the armor or container is malformed.
+    pub fn from_armor(text: &str) -> Result<Self, Error> {
+        Self::from_bytes(Armor::decode_kind(text, ArmorKind::Message)?.data())
+    }
+}
+
+impl PublicKey {
+    /// The `to_armor` function armors the key's SubjectPublicKeyInfo as a `SOP-KODE PUBLIC KEY` block
+    /// with a `Fingerprint` header.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the armored text.
+    pub fn to_armor(&self) -> String {
+        let headers = vec![("Fingerprint".to_string(), hex(&self.fingerprint()))];
+        Armor { kind: ArmorKind::PublicKey, headers, data: self.to_spki_der() }.encode()
+    }
+
+    /// The `from_armor` function parses a `SOP-KODE PUBLIC KEY` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The armored text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or an error if the armor or encoding is malformed.
+    pub fn from_armor(text: &str) -> Result<Self, Error> {
+        Self::from_spki_der(Armor::decode_kind(text, ArmorKind::PublicKey)?.data())
+    }
+}
+
+impl RSA {
+    /// The `to_armor` function armors the key pair's PKCS#8 PrivateKeyInfo as a `SOP-KODE PRIVATE KEY`
+    /// block with a `Fingerprint` header. The key is not encrypted.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the armored text, or `Error::MissingPrimeFactors` if the private
+    ///   key does not hold `p` and `q`.
+    pub fn to_armor(&self) -> Result<String, Error> {
+        let headers = vec![("Fingerprint".to_string(), hex(&self.public_key.fingerprint()))];
+        Ok(Armor { kind: ArmorKind::PrivateKey, headers, data: self.to_pkcs8_der()? }.encode())
+    }
+
+    /// The `from_armor` function parses a `SOP-KODE PRIVATE KEY` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The armored text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, or an error if the armor or encoding is malformed.
+    pub fn from_armor(text: &str) -> Result<Self, Error> {
+        Self::from_pkcs8_der(Armor::decode_kind(text, ArmorKind::PrivateKey)?.data())
+    }
+}
+
+/// The `signature_to_armor` function armors a PKCS#1 v1.5 or PSS signature as a `SOP-KODE SIGNATURE` block.
+///
+/// # Arguments
+///
+/// * `signature` - The signature bytes, as returned by `RSA::sign` or `RSA::sign_pss`.
+///
+/// # Returns
+///
+/// * `String` - Returns the armored text.
+pub fn signature_to_armor(signature: &[u8]) -> String {
+    Armor::new(ArmorKind::Signature, signature.to_vec()).encode()
+}
+
+/// The `signature_from_armor` function parses a `SOP-KODE SIGNATURE` block. Headers are informational only.
+///
+/// # Arguments
+///
+/// * `text` - The armored text.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the signature bytes, or an error if the armor is malformed or holds
+///   another kind of object.
+pub fn signature_from_armor(text: &str) -> Result<Vec<u8>, Error> {
+    Ok(Armor::decode_kind(text, ArmorKind::Signature)?.data().to_vec())
+}
@@ -0,0 +1,121 @@
+use num_bigint::BigUint;
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use crate::hash::HashAlg;
+use crate::Error;
+use super::encryption::PublicKey;
+use super::keys::RSA;
+use super::math::random_unit;
+use super::signature::emsa_pkcs1_v15_encode;
+use super::utils::to_fixed_bytes;
+
+/// The `BlindingFactor` struct holds the inverse of the random `r` a message was blinded with for a
+/// Chaum blind signature. The requester blinds the encoded message `m` as `m * r^e mod n`, which looks
+/// random to the signer; the signer raises it to `d`, giving `m^d * r`, and `PublicKey::unblind`
+/// divides `r` out again. The result is an ordinary RSASSA-PKCS1-v1_5 signature that
+/// `PublicKey::verify` accepts, yet the signer cannot link it to the request. The requester keeps
+/// the factor to unblind the signature; anyone holding it can make that link.
+pub struct BlindingFactor {
+    inverse: BigUint,
+}
+
+impl PublicKey {
+    /// The `blind` function hides a message from the signer before it is sent for signing.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to have signed.
+    /// * `hash` - The hash function the signature will be verified with.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Vec<u8>, BlindingFactor), Error>` - Returns the blinded message, `k` big-endian bytes to
+    ///   send to the signer, and the factor to keep for `unblind`, or `Error::KeyTooSmall` if the modulus
+    ///   cannot hold the encoded digest or is too small to blind with.
+    pub fn blind(&self, message: &[u8], hash: HashAlg) -> Result<(Vec<u8>, BlindingFactor), Error> {
+        self.blind_with_rng(message, hash, &mut thread_rng())
+    }
+
+    /// The `blind_with_rng` function blinds a message like `blind`, drawing `r` from the given random
+    /// number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to have signed.
+    /// * `hash` - The hash function the signature will be verified with.
+    /// * `rng` - The random number generator used to draw the blinding factor.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Vec<u8>, BlindingFactor), Error>` - Returns the blinded message and the factor, or an
+    ///   error as described for `blind`.
+    pub fn blind_with_rng<R: RngCore + CryptoRng + ?Sized>(
+        &self,
+        message: &[u8],
+        hash: HashAlg,
+        rng: &mut R,
+    ) -> Result<(Vec<u8>, BlindingFactor), Error> {
+        // A modulus below 3 leaves no blinding factor to draw
+        if self.n <= BigUint::from(2u8) {
+            return Err(Error::KeyTooSmall);
+        }
+        let k = self.byte_len();
+        let encoded = BigUint::from_bytes_be(&emsa_pkcs1_v15_encode(message, hash, k)?);
+        let (r, inverse) = random_unit(&self.n, rng)?;
+
+        // m * r^e mod n
+        let blinded = (encoded * self.encrypt(&r, self)?) % &self.n;
+        Ok((to_fixed_bytes(&blinded, k), BlindingFactor { inverse }))
+    }
+
+    /// The `unblind` function removes the blinding factor from the signer's answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `blind_signature` - The signer's answer from `RSA::sign_blinded`.
+    /// * `factor` - The factor `blind` returned with the blinded message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the signature on the original message, to be checked with
+    ///   `verify`, or `Error::InvalidSignature` if the answer is not `k` bytes smaller than the modulus.
+    pub fn unblind(&self, blind_signature: &[u8], factor: &BlindingFactor) -> Result<Vec<u8>, Error> {
+        let k = self.byte_len();
+        if blind_signature.len() != k {
+            return Err(Error::InvalidSignature);
+        }
+        let blind_signature = BigUint::from_bytes_be(blind_signature);
+        if blind_signature >= self.n {
+            return Err(Error::InvalidSignature);
+        }
+
+        // m^d * r * r^-1 mod n
+        let signature = (blind_signature * &factor.inverse) % &self.n;
+        Ok(to_fixed_bytes(&signature, k))
+    }
+}
+
+impl RSA {
+    /// The `sign_blinded` function signs a blinded message with textbook RSA, `m'^d mod n`. The signer
+    /// learns nothing about the message behind it.
+    ///
+    /// It signs whatever it is given, so a key used for blind signing must not be used for anything
+    /// else: a blinded request could just as well be a ciphertext to decrypt.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_message` - The blinded message from `PublicKey::blind`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the blind signature as `k` big-endian bytes, or
+    ///   `Error::MessageTooLong` if the blinded message is not smaller than the modulus.
+    pub fn sign_blinded(&self, blinded_message: &[u8]) -> Result<Vec<u8>, Error> {
+        let blinded_message = BigUint::from_bytes_be(blinded_message);
+        if blinded_message >= self.public_key.n {
+            return Err(Error::MessageTooLong);
+        }
+        let blind_signature = self.private_key().decrypt(&blinded_message);
+        Ok(to_fixed_bytes(&blind_signature, self.public_key.byte_len()))
+    }
+}
@@ -0,0 +1,162 @@
+use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_traits::{One, Zero};
+use rand::rngs::ThreadRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use super::{binary_extended_gcd, generate_prime_with};
+use super::keys::RSA;
+use crate::Error;
+
+/// The smallest modulus size, in bits, the builder will generate.
+pub const MIN_KEY_BITS: usize = 32;
+
+/// The number of candidate primes drawn for each factor before giving up on finding one where
+/// `p - 1` is coprime to the public exponent and `q` differs from `p`.
+const MAX_PRIME_ATTEMPTS: usize = 64;
+
+/// Checks that a modulus of exactly `bits` bits can be generated from two primes of `bits / 2` bits.
+pub(crate) fn check_key_bits(bits: usize) -> Result<(), Error> {
+    if bits < MIN_KEY_BITS {
+        return Err(Error::BitsTooSmall { bits, min: MIN_KEY_BITS });
+    }
+    if !bits.is_multiple_of(2) {
+        return Err(Error::OddBitSize(bits));
+    }
+    Ok(())
+}
+
+/// The RsaBuilder struct configures and generates an RSA system.
+///
+/// # Example
+///
+/// ```
+/// use sop_kode::rsa::RsaBuilder;
+///
+/// let rsa = RsaBuilder::new()
+///     .bits(512)
+///     .public_exponent(3)
+///     .mr_rounds(20)
+///     .rng(rand::thread_rng())
+///     .build()
+///     .expect("Failed to create RSA");
+/// assert_eq!(rsa.public_key.e, 3u32.into());
+/// ```
+pub struct RsaBuilder<R = ThreadRng> {
+    bits: usize,
+    public_exponent: BigUint,
+    mr_rounds: usize,
+    rng: R,
+}
+
+impl RsaBuilder<ThreadRng> {
+    /// Constructs a new RsaBuilder with a 2048-bit modulus, e = 65537, 5 Miller-Rabin rounds and `thread_rng`.
+    pub fn new() -> Self {
+        Self {
+            bits: 2048,
+            public_exponent: BigUint::from(65537u64),
+            mr_rounds: 5,
+            rng: rand::thread_rng(),
+        }
+    }
+}
+
+impl Default for RsaBuilder<ThreadRng> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: RngCore + CryptoRng> RsaBuilder<R> {
+    /// Sets the size of the modulus in bits.
+    pub fn bits(mut self, bits: usize) -> Self {
+        self.bits = bits;
+        self
+    }
+
+    /// Sets the public exponent `e`.
+    pub fn public_exponent(mut self, e: u64) -> Self {
+        self.public_exponent = BigUint::from(e);
+        self
+    }
+
+    /// Sets the number of Miller-Rabin rounds used when testing candidate primes. Every round tests a
+    /// base drawn from the builder's random number generator.
+    pub fn mr_rounds(mut self, rounds: usize) -> Self {
+        self.mr_rounds = rounds;
+        self
+    }
+
+    /// Sets the random number generator used to draw candidate primes and their Miller-Rabin bases.
+    pub fn rng<R2: RngCore + CryptoRng>(self, rng: R2) -> RsaBuilder<R2> {
+        RsaBuilder {
+            bits: self.bits,
+            public_exponent: self.public_exponent,
+            mr_rounds: self.mr_rounds,
+            rng,
+        }
+    }
+
+    /// Generates the RSA system.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns an RSA system, or the reason the parameters could not be used.
+    pub fn build(mut self) -> Result<RSA, Error> {
+        check_key_bits(self.bits)?;
+        if self.public_exponent <= BigUint::one() || (&self.public_exponent % 2u32).is_zero() {
+            return Err(Error::InvalidPublicExponent(self.public_exponent));
+        }
+        if self.mr_rounds == 0 {
+            return Err(Error::ZeroRounds);
+        }
+
+        // Each prime gets its own generator seeded from the builder's, so the two can be drawn in
+        // parallel and a seeded builder still gives the same key every time
+        let mut p_rng = self.child_rng();
+        let mut q_rng = self.child_rng();
+        let (bits, rounds, e) = (self.bits / 2, self.mr_rounds, &self.public_exponent);
+        let (p, q) = rayon::join(
+            || generate_factor(bits, rounds, e, &mut p_rng, None),
+            || generate_factor(bits, rounds, e, &mut q_rng, None),
+        );
+        let p = p?;
+        let q = match q? {
+            q if q == p => generate_factor(bits, rounds, e, &mut q_rng, Some(&p))?,
+            q => q,
+        };
+
+        RSA::from_primes(p, q, self.public_exponent)
+    }
+
+    /// Seeds a ChaCha20 generator from the builder's random number generator.
+    fn child_rng(&mut self) -> ChaCha20Rng {
+        let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        self.rng.fill_bytes(&mut seed);
+        ChaCha20Rng::from_seed(seed)
+    }
+}
+
+/// Draws a prime `p` of `bits` bits for which `gcd(e, p - 1) = 1`, and which differs from the other
+/// factor if it is already known.
+fn generate_factor(
+    bits: usize,
+    rounds: usize,
+    e: &BigUint,
+    rng: &mut ChaCha20Rng,
+    other: Option<&BigUint>,
+) -> Result<BigUint, Error> {
+    let e = e.to_bigint().unwrap();
+    for _ in 0..MAX_PRIME_ATTEMPTS {
+        let p = generate_prime_with(bits, rounds, rng)?;
+        if other == Some(&p) {
+            continue;
+        }
+        let p_minus_one = (&p - BigUint::one()).to_bigint().unwrap();
+        let (mut x, mut y) = (BigInt::zero(), BigInt::zero());
+        if binary_extended_gcd(&e, &p_minus_one, &mut x, &mut y).is_one() {
+            return Ok(p);
+        }
+    }
+    Err(Error::NonInvertibleExponent)
+}
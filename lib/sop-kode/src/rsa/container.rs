@@ -0,0 +1,237 @@
+use num_bigint::BigUint;
+
+use crate::hash::{sha256, sha256::DIGEST_SIZE};
+use crate::Error;
+use super::encryption::PublicKey;
+use super::padding::PaddingScheme;
+
+/// The magic number every ciphertext container starts with.
+pub const MAGIC: &[u8; 4] = b"SOPK";
+/// The container format version written by this crate.
+pub const VERSION: u8 = 1;
+
+/// The size of the header: magic, version, padding scheme id, fingerprint and block size.
+pub(crate) const HEADER_SIZE: usize = MAGIC.len() + 1 + 1 + DIGEST_SIZE + 4;
+/// The size of the trailer: block count and original length.
+pub(crate) const TRAILER_SIZE: usize = 8 + 8;
+
+/// The `Ciphertext` struct is the binary container of an encrypted message. It records which key and
+/// padding scheme the message was encrypted with, so it can be stored and exchanged on its own.
+///
+/// The encoding is, with all integers big-endian:
+///
+/// | Field              | Size            |
+/// |--------------------|-----------------|
+/// | magic `SOPK`       | 4               |
+/// | version            | 1               |
+/// | padding scheme id  | 1               |
+/// | key fingerprint    | 32              |
+/// | block size `k`     | 4               |
+/// | blocks             | `k` each        |
+/// | block count        | 8               |
+/// | original length    | 8               |
+///
+/// The count and length come after the blocks, so a stream can be encrypted without knowing its
+/// length up front, see `PublicKey::encrypt_stream`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ciphertext {
+    padding_id: u8,
+    fingerprint: [u8; DIGEST_SIZE],
+    length: u64,
+    block_size: usize,
+    blocks: Vec<BigUint>,
+}
+
+/// The fields of a container header.
+pub(crate) struct Header {
+    pub(crate) padding_id: u8,
+    pub(crate) fingerprint: [u8; DIGEST_SIZE],
+    pub(crate) block_size: usize,
+}
+
+impl Header {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.padding_id);
+        bytes.extend_from_slice(&self.fingerprint);
+        bytes.extend_from_slice(&(self.block_size as u32).to_be_bytes());
+        bytes
+    }
+
+    pub(crate) fn decode(bytes: &[u8; HEADER_SIZE]) -> Result<Self, Error> {
+        let (magic, rest) = bytes.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Err(Error::InvalidCiphertext("not a ciphertext container"));
+        }
+        if rest[0] != VERSION {
+            return Err(Error::InvalidCiphertext("unsupported version"));
+        }
+        let padding_id = rest[1];
+        if PaddingScheme::from_id(padding_id).is_none() {
+            return Err(Error::InvalidCiphertext("unknown padding scheme"));
+        }
+        let (fingerprint, block_size) = rest[2..].split_at(DIGEST_SIZE);
+        let block_size = u32::from_be_bytes(block_size.try_into().unwrap()) as usize;
+        if block_size == 0 {
+            return Err(Error::InvalidCiphertext("block size is zero"));
+        }
+
+        Ok(Header { padding_id, fingerprint: fingerprint.try_into().unwrap(), block_size })
+    }
+}
+
+/// Encodes the trailer that follows the blocks.
+pub(crate) fn encode_trailer(block_count: u64, length: u64) -> [u8; TRAILER_SIZE] {
+    let mut trailer = [0u8; TRAILER_SIZE];
+    trailer[..8].copy_from_slice(&block_count.to_be_bytes());
+    trailer[8..].copy_from_slice(&length.to_be_bytes());
+    trailer
+}
+
+/// Decodes the trailer into the block count and the original length.
+pub(crate) fn decode_trailer(trailer: &[u8; TRAILER_SIZE]) -> (u64, u64) {
+    let (block_count, length) = trailer.split_at(8);
+    (u64::from_be_bytes(block_count.try_into().unwrap()), u64::from_be_bytes(length.try_into().unwrap()))
+}
+
+impl Ciphertext {
+    /// The `new` function wraps encrypted blocks in a container.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key` - The recipient's key, which sets the fingerprint and the block size.
+    /// * `padding` - The padding scheme the blocks were encrypted with.
+    /// * `length` - The length of the original message in bytes.
+    /// * `blocks` - The encrypted blocks, each smaller than the recipient's modulus.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the container, or `Error::InvalidCiphertext` if a block is
+    ///   not smaller than the modulus and so cannot be encoded in `k` bytes.
+    pub fn new(public_key: &PublicKey, padding: &PaddingScheme, length: u64, blocks: Vec<BigUint>) -> Result<Self, Error> {
+        if blocks.iter().any(|block| block >= &public_key.n) {
+            return Err(Error::InvalidCiphertext("block is not smaller than the modulus"));
+        }
+
+        Ok(Ciphertext {
+            padding_id: padding.id(),
+            fingerprint: public_key.fingerprint(),
+            length,
+            block_size: public_key.byte_len(),
+            blocks,
+        })
+    }
+
+    /// Returns the padding scheme the message was encrypted with. The container does not hold the OAEP
+    /// label, so the scheme comes back without one; see `is_labelled`.
+    pub fn padding(&self) -> PaddingScheme {
+        PaddingScheme::from_id(self.padding_id).expect("the padding scheme id is checked when the container is made")
+    }
+
+    /// Returns `true` if the message was bound to a nonempty OAEP label, which has to be passed to
+    /// `RSA::decrypt_bytes_with_label`.
+    pub fn is_labelled(&self) -> bool {
+        PaddingScheme::is_labelled_id(self.padding_id)
+    }
+
+    /// Returns the fingerprint of the recipient's key, see `PublicKey::fingerprint`.
+    pub fn fingerprint(&self) -> &[u8; DIGEST_SIZE] {
+        &self.fingerprint
+    }
+
+    /// Returns the length of the original message in bytes.
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns true if the original message was empty.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Returns the width `k` of every encoded block, the byte length of the recipient's modulus.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Returns the encrypted blocks.
+    pub fn blocks(&self) -> &[BigUint] {
+        &self.blocks
+    }
+
+    /// Returns the header fields of the container.
+    pub(crate) fn header(&self) -> Header {
+        Header { padding_id: self.padding_id, fingerprint: self.fingerprint, block_size: self.block_size }
+    }
+
+    /// The `to_bytes` function encodes the container.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - Returns the header, every block as `k` big-endian bytes and the trailer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header().encode();
+        bytes.reserve(self.blocks.len() * self.block_size + TRAILER_SIZE);
+
+        for block in &self.blocks {
+            let block = block.to_bytes_be();
+            bytes.resize(bytes.len() + self.block_size - block.len(), 0);
+            bytes.extend_from_slice(&block);
+        }
+
+        bytes.extend_from_slice(&encode_trailer(self.blocks.len() as u64, self.length));
+        bytes
+    }
+
+    /// The `from_bytes` function decodes a container. Only the structure is checked here; whether the
+    /// blocks belong to a key is checked when decrypting.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded container.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the container, or `Error::InvalidCiphertext` if the input is
+    ///   truncated, the blocks do not match the count or the magic number, version or padding scheme is unknown.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_SIZE + TRAILER_SIZE {
+            return Err(Error::InvalidCiphertext("truncated"));
+        }
+        let (header, rest) = bytes.split_at(HEADER_SIZE);
+        let (blocks, trailer) = rest.split_at(rest.len() - TRAILER_SIZE);
+        let Header { padding_id, fingerprint, block_size } = Header::decode(header.try_into().unwrap())?;
+        let (block_count, length) = decode_trailer(trailer.try_into().unwrap());
+
+        if blocks.len() as u128 != block_count as u128 * block_size as u128 {
+            return Err(Error::InvalidCiphertext("blocks do not match the block count"));
+        }
+
+        let blocks = blocks.chunks(block_size).map(BigUint::from_bytes_be).collect();
+
+        Ok(Ciphertext { padding_id, fingerprint, length, block_size, blocks })
+    }
+}
+
+impl PublicKey {
+    /// The `fingerprint` function calculates the SHA-256 digest of the key's SubjectPublicKeyInfo DER
+    /// encoding, the same value as `openssl pkey -pubin -outform DER | sha256sum`.
+    ///
+    /// # Returns
+    ///
+    /// * `[u8; 32]` - Returns the fingerprint.
+    pub fn fingerprint(&self) -> [u8; DIGEST_SIZE] {
+        sha256(&self.to_spki_der())
+    }
+
+    /// The `byte_len` function returns the byte length `k` of the modulus, the width of an encrypted block.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - Returns the number of bytes needed to hold `n`.
+    pub fn byte_len(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+}
@@ -1,5 +1,13 @@
-use num_bigint::BigUint;
-use crate::calculate_chunk_size;
+use num_bigint::{BigUint, ToBigInt};
+use num_traits::{One, Zero};
+use rand::{thread_rng, CryptoRng, RngCore};
+use rayon::prelude::*;
+use crate::{calculate_chunk_size, frame_bytes, mod_inverse, Error};
+use super::builder::MIN_KEY_BITS;
+use super::container::Ciphertext;
+use super::math::{ct_modpow, random_unit};
+use super::padding::PaddingScheme;
+use super::validation::{ValidationFailure, ValidationReport};
 
 /// The PublicKey struct represents a public key in RSA encryption.
 /// It contains two BigUint values, `n` and `e`.
@@ -10,10 +18,26 @@ pub struct PublicKey {
 }
 
 /// The PrivateKey struct represents a private key in RSA encryption.
-/// It contains two BigUint values, `n` and `d`.
+/// It contains two BigUint values, `n` and `d`, and optionally the prime factors of `n`
+/// together with the precomputed values used for Chinese Remainder Theorem decryption, and
+/// optionally the public exponent `e` used to blind every private-key operation.
+#[derive(Clone, Debug)]
 pub struct PrivateKey {
     n: BigUint,
     d: BigUint,
+    crt: Option<CrtComponents>,
+    blinding: Option<BigUint>,
+}
+
+/// The CrtComponents struct holds the prime factors `p` and `q` of the modulus together with
+/// `dP = d mod (p - 1)`, `dQ = d mod (q - 1)` and `qInv = q^-1 mod p`.
+#[derive(Clone, Debug)]
+struct CrtComponents {
+    p: BigUint,
+    q: BigUint,
+    dp: BigUint,
+    dq: BigUint,
+    qinv: BigUint,
 }
 
 impl PublicKey {
@@ -26,9 +50,187 @@ impl PublicKey {
     ///
     /// # Returns
     ///
-    /// * `BigUint` - Returns the encrypted message.
-    pub fn encrypt(&self, message: &BigUint, public_key: &PublicKey) -> BigUint {
-        message.modpow(&public_key.e, &public_key.n)
+    /// * `Result<BigUint, Error>` - Returns the encrypted message, or `Error::MessageTooLong` if the
+    ///   message is not smaller than the modulus.
+    pub fn encrypt(&self, message: &BigUint, public_key: &PublicKey) -> Result<BigUint, Error> {
+        if message >= &public_key.n {
+            return Err(Error::MessageTooLong);
+        }
+        Ok(message.modpow(&public_key.e, &public_key.n))
+    }
+
+    /// The `chunk_size` function calculates how many message bytes fit in one block for this key.
+    ///
+    /// # Arguments
+    ///
+    /// * `padding` - The padding scheme the chunks are encrypted with.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, Error>` - Returns the chunk size in bytes, or `Error::KeyTooSmall` if the modulus
+    ///   cannot hold a single byte next to the padding.
+    pub fn chunk_size(&self, padding: &PaddingScheme) -> Result<usize, Error> {
+        match padding.chunk_size(&self.n) {
+            0 => Err(Error::KeyTooSmall),
+            chunk_size => Ok(chunk_size),
+        }
+    }
+
+    /// The `encrypt_message` function encrypts a message to the owner of this key with PKCS#1 v1.5
+    /// padding. Every size is derived from this key's modulus, so no key pair is needed to encrypt.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to encrypt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the ciphertext container, or `Error::KeyTooSmall` if the
+    ///   modulus cannot hold a chunk.
+    pub fn encrypt_message(&self, message: &str) -> Result<Ciphertext, Error> {
+        self.encrypt_bytes(message.as_bytes())
+    }
+
+    /// The `encrypt_bytes` function encrypts bytes to the owner of this key with PKCS#1 v1.5 padding.
+    /// The bytes are framed with their length and chunked to fit this key's modulus, so any payload
+    /// round-trips exactly. Use `encrypt_stream` for input that does not fit in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to encrypt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the ciphertext container, or `Error::KeyTooSmall` if the
+    ///   modulus cannot hold a chunk.
+    pub fn encrypt_bytes(&self, bytes: &[u8]) -> Result<Ciphertext, Error> {
+        self.encrypt_bytes_with_rng(bytes, &PaddingScheme::default(), &mut thread_rng())
+    }
+
+    /// The `encrypt_bytes_with_rng` function encrypts bytes like `encrypt_bytes`, with the given padding
+    /// scheme and source of padding randomness. A seeded generator makes the ciphertext reproducible.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to encrypt.
+    /// * `padding` - The padding scheme to encrypt the chunks with.
+    /// * `rng` - The random number generator used to draw the padding bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the ciphertext container, or `Error::KeyTooSmall` if the
+    ///   modulus cannot hold a chunk.
+    pub fn encrypt_bytes_with_rng<R: RngCore + CryptoRng + ?Sized>(
+        &self,
+        bytes: &[u8],
+        padding: &PaddingScheme,
+        rng: &mut R,
+    ) -> Result<Ciphertext, Error> {
+        let blocks = self
+            .encode_chunks(bytes, padding, rng)?
+            .iter()
+            .map(|encoded| self.encrypt(&BigUint::from_bytes_be(encoded), self))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ciphertext::new(self, padding, bytes.len() as u64, blocks)
+    }
+
+    /// The `par_encrypt_message` function encrypts a message like `encrypt_message`, but encrypts the
+    /// chunks in parallel on the rayon thread pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to encrypt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns a container as described for `encrypt_message`.
+    pub fn par_encrypt_message(&self, message: &str) -> Result<Ciphertext, Error> {
+        self.par_encrypt_bytes(message.as_bytes())
+    }
+
+    /// The `par_encrypt_bytes` function encrypts bytes like `encrypt_bytes`, but encrypts the chunks in
+    /// parallel on the rayon thread pool. The blocks keep their order.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to encrypt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns a container as described for `encrypt_bytes`.
+    pub fn par_encrypt_bytes(&self, bytes: &[u8]) -> Result<Ciphertext, Error> {
+        self.par_encrypt_bytes_with_rng(bytes, &PaddingScheme::default(), &mut thread_rng())
+    }
+
+    /// The `par_encrypt_bytes_with_rng` function encrypts bytes like `encrypt_bytes_with_rng`, but
+    /// encrypts the chunks in parallel. The padding is drawn from `rng` in order before the parallel
+    /// part, so a seeded generator gives the same container as `encrypt_bytes_with_rng`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to encrypt.
+    /// * `padding` - The padding scheme to encrypt the chunks with.
+    /// * `rng` - The random number generator used to draw the padding bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ciphertext, Error>` - Returns the same container as `encrypt_bytes_with_rng`.
+    pub fn par_encrypt_bytes_with_rng<R: RngCore + CryptoRng + ?Sized>(
+        &self,
+        bytes: &[u8],
+        padding: &PaddingScheme,
+        rng: &mut R,
+    ) -> Result<Ciphertext, Error> {
+        let blocks = self
+            .encode_chunks(bytes, padding, rng)?
+            .par_iter()
+            .map(|encoded| self.encrypt(&BigUint::from_bytes_be(encoded), self))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ciphertext::new(self, padding, bytes.len() as u64, blocks)
+    }
+
+    /// Frames the bytes, splits them into chunks and pads every chunk for encryption.
+    fn encode_chunks<R: RngCore + CryptoRng + ?Sized>(
+        &self,
+        bytes: &[u8],
+        padding: &PaddingScheme,
+        rng: &mut R,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let chunk_size = self.chunk_size(padding)?;
+        frame_bytes(bytes, chunk_size)?
+            .chunks(chunk_size)
+            .map(|chunk| padding.encode_chunk(chunk, self.byte_len(), rng))
+            .collect()
+    }
+
+    /// The `validate` function checks that the public key is sound: `e` must be odd, greater than 1
+    /// and smaller than `n`, and `n` must be odd and at least `MIN_KEY_BITS` bits.
+    ///
+    /// # Returns
+    ///
+    /// * `ValidationReport` - Returns a report listing every check the key failed.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        if self.e <= BigUint::one() {
+            report.fail(ValidationFailure::ExponentTooSmall);
+        }
+        if !self.e.bit(0) {
+            report.fail(ValidationFailure::ExponentEven);
+        }
+        if self.e >= self.n {
+            report.fail(ValidationFailure::ExponentTooLarge);
+        }
+        if !self.n.bit(0) {
+            report.fail(ValidationFailure::ModulusEven);
+        }
+        if self.n.bits() < MIN_KEY_BITS as u64 {
+            report.fail(ValidationFailure::ModulusTooSmall { bits: self.n.bits(), min: MIN_KEY_BITS as u64 });
+        }
+
+        report
     }
 }
 
@@ -44,10 +246,146 @@ impl PrivateKey {
     ///
     /// * `PrivateKey` - Returns a new PrivateKey.
     pub fn new(n: BigUint, d: BigUint) -> Self {
-        Self { n, d }
+        Self { n, d, crt: None, blinding: None }
+    }
+
+    /// Constructs a new PrivateKey from the prime factors `p` and `q` of `n` and the private exponent `d`.
+    /// The CRT values `dP`, `dQ` and `qInv` are precomputed so that `decrypt` can work modulo `p` and `q`
+    /// separately instead of doing a full-size exponentiation modulo `n`.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The first prime factor of `n`.
+    /// * `q` - The second prime factor of `n`.
+    /// * `d` - The `d` value of the private key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PrivateKey, Error>` - Returns a new PrivateKey with CRT components, or
+    ///   `Error::PrimeCollision` if `p` and `q` are equal, or `Error::InvalidPrimeFactors` if either is
+    ///   smaller than 2 or they are not coprime.
+    pub fn from_primes(p: BigUint, q: BigUint, d: BigUint) -> Result<Self, Error> {
+        if p == q {
+            return Err(Error::PrimeCollision);
+        }
+
+        let one = BigUint::from(1u8);
+        if p <= one || q <= one {
+            return Err(Error::InvalidPrimeFactors);
+        }
+        let qinv = mod_inverse(q.to_bigint().unwrap(), p.to_bigint().unwrap())
+            .to_biguint()
+            .unwrap();
+        if qinv.is_zero() {
+            return Err(Error::InvalidPrimeFactors);
+        }
+
+        let n = &p * &q;
+        let dp = &d % (&p - &one);
+        let dq = &d % (&q - &one);
+
+        Ok(Self {
+            n,
+            d,
+            crt: Some(CrtComponents { p, q, dp, dq, qinv }),
+            blinding: None,
+        })
+    }
+
+    /// Returns this key with blinding turned on. Every `decrypt` then works on `c * r^e mod n` for a
+    /// fresh random `r` and divides `r` out of the result, so its timing no longer depends on the
+    /// ciphertext an attacker chose. Keys made by `RSA` are blinded already. A wrong `e` would make
+    /// every blinded result wrong, so `e` is checked against the key: with the prime factors by testing
+    /// `e * dP ≡ 1 (mod p - 1)` and `e * dQ ≡ 1 (mod q - 1)`, which costs next to nothing, and without
+    /// them by testing `2^(e * d) ≡ 2 (mod n)`, which costs a full private-key exponentiation.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The public exponent belonging to this key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PrivateKey, Error>` - Returns the key with blinding turned on, `Error::KeyTooSmall` if
+    ///   the modulus is smaller than 3 and leaves no blinding factor to draw, or `Error::ExponentMismatch`
+    ///   if `e` does not belong to the key.
+    pub fn with_blinding(mut self, e: BigUint) -> Result<Self, Error> {
+        if self.n <= BigUint::from(2u8) {
+            return Err(Error::KeyTooSmall);
+        }
+        let matches = match &self.crt {
+            Some(crt) => {
+                let inverts = |dx: &BigUint, x: &BigUint| {
+                    let order = x - BigUint::one();
+                    (&e * dx) % &order == BigUint::one() % &order
+                };
+                inverts(&crt.dp, &crt.p) && inverts(&crt.dq, &crt.q)
+            }
+            None => {
+                let r = BigUint::from(2u8);
+                self.exponentiate(&r.modpow(&e, &self.n)) == r
+            }
+        };
+        if !matches {
+            return Err(Error::ExponentMismatch);
+        }
+        self.blinding = Some(e);
+        Ok(self)
+    }
+
+    /// Returns a copy of this key with blinding turned off, so that `decrypt` exponentiates the
+    /// ciphertext itself. Mostly useful for measuring what blinding costs.
+    ///
+    /// # Returns
+    ///
+    /// * `PrivateKey` - Returns a PrivateKey that does not blind.
+    pub fn without_blinding(&self) -> Self {
+        Self { blinding: None, ..self.clone() }
+    }
+
+    /// Returns a copy of this key without the CRT components, so that `decrypt` uses the plain
+    /// `c^d mod n` exponentiation. Mostly useful for comparing the two decryption paths.
+    ///
+    /// # Returns
+    ///
+    /// * `PrivateKey` - Returns a PrivateKey holding only `n` and `d`.
+    pub fn without_crt(&self) -> Self {
+        Self { crt: None, ..self.clone() }
+    }
+
+    /// Returns the modulus `n` of the private key.
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// Returns the private exponent `d`.
+    pub fn d(&self) -> &BigUint {
+        &self.d
+    }
+
+    /// Returns the prime factors `p` and `q` of the modulus, if the key holds them.
+    pub fn primes(&self) -> Option<(&BigUint, &BigUint)> {
+        self.crt.as_ref().map(|crt| (&crt.p, &crt.q))
+    }
+
+    /// Returns the CRT values `dP`, `dQ` and `qInv`, if the key holds the prime factors.
+    pub fn crt_values(&self) -> Option<(&BigUint, &BigUint, &BigUint)> {
+        self.crt.as_ref().map(|crt| (&crt.dp, &crt.dq, &crt.qinv))
+    }
+
+    /// Returns `true` if the key holds the prime factors and can decrypt using the CRT.
+    pub fn has_crt(&self) -> bool {
+        self.crt.is_some()
+    }
+
+    /// Returns `true` if the key blinds the ciphertext before every exponentiation.
+    pub fn has_blinding(&self) -> bool {
+        self.blinding.is_some()
     }
 
     /// The `decrypt` function is used to decrypt a message using a private key.
+    /// If the key holds its prime factors, the Chinese Remainder Theorem is used together with
+    /// Garner's formula to recombine the two half-size exponentiations. If the key blinds, the
+    /// blinding factor is drawn from `thread_rng`.
     ///
     /// # Arguments
     ///
@@ -57,7 +395,47 @@ impl PrivateKey {
     ///
     /// * `BigUint` - Returns the decrypted message.
     pub fn decrypt(&self, ciphertext: &BigUint) -> BigUint {
-        ciphertext.modpow(&self.d, &self.n)
+        self.decrypt_with_rng(ciphertext, &mut thread_rng())
+    }
+
+    /// The `decrypt_with_rng` function decrypts like `decrypt`, drawing the blinding factor from the
+    /// given random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `ciphertext` - A BigUint value representing the message to be decrypted.
+    /// * `rng` - The random number generator used to draw the blinding factor.
+    ///
+    /// # Returns
+    ///
+    /// * `BigUint` - Returns the decrypted message.
+    pub fn decrypt_with_rng<R: RngCore + CryptoRng + ?Sized>(&self, ciphertext: &BigUint, rng: &mut R) -> BigUint {
+        match &self.blinding {
+            Some(e) => {
+                // (c * r^e)^d = m * r, so multiplying by r^-1 leaves m
+                let (r, inverse) = random_unit(&self.n, rng).expect("with_blinding checks the modulus");
+                let blinded = (ciphertext * r.modpow(e, &self.n)) % &self.n;
+                (self.exponentiate(&blinded) * inverse) % &self.n
+            }
+            None => self.exponentiate(ciphertext),
+        }
+    }
+
+    /// Raises the ciphertext to `d`, with the CRT if the key holds its prime factors. The exponentiations
+    /// run in constant time with respect to the secret exponents.
+    fn exponentiate(&self, ciphertext: &BigUint) -> BigUint {
+        match &self.crt {
+            Some(crt) => {
+                let m1 = ct_modpow(ciphertext, &crt.dp, &crt.p);
+                let m2 = ct_modpow(ciphertext, &crt.dq, &crt.q);
+
+                // Garner's formula: h = qInv * (m1 - m2) mod p, m = m2 + h * q
+                let diff = (&m1 + &crt.p - (&m2 % &crt.p)) % &crt.p;
+                let h = (&crt.qinv * diff) % &crt.p;
+                m2 + h * &crt.q
+            }
+            None => ct_modpow(ciphertext, &self.d, &self.n),
+        }
     }
 
     /// The `get_chunk_size` function is used to calculate the chunk size for a message.
@@ -0,0 +1,182 @@
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::hash::sha256;
+use crate::Error;
+use crate::base64;
+use crate::json::{self, Value};
+use super::encryption::{PrivateKey, PublicKey};
+use super::keys::RSA;
+
+/// The JWK key type of RSA keys (RFC 7518 §6.1).
+pub const KEY_TYPE: &str = "RSA";
+
+/// Encodes an integer as the unsigned big-endian Base64url value of a JWK member (RFC 7518 §6.3).
+fn encode_integer(value: &BigUint) -> String {
+    base64::encode_url(&value.to_bytes_be())
+}
+
+/// Writes the members as a JSON object, sorted by name.
+fn encode_members(mut members: Vec<(&str, String)>) -> String {
+    members.sort_by_key(|&(name, _)| name);
+    json::encode_object(&members)
+}
+
+/// Parses the JSON text and checks that it is an object holding an RSA key.
+fn parse_object(jwk: &str) -> Result<Value, Error> {
+    let object = match json::parse(jwk) {
+        Some(object @ Value::Object(_)) => object,
+        _ => return Err(Error::InvalidJwk("not a JSON object")),
+    };
+    if object.get("kty").and_then(Value::as_str) != Some(KEY_TYPE) {
+        return Err(Error::InvalidJwk("kty is not RSA"));
+    }
+    Ok(object)
+}
+
+/// Reads an optional integer member, which must use the minimum number of octets.
+fn read_optional_integer(object: &Value, name: &'static str) -> Result<Option<BigUint>, Error> {
+    let Some(value) = object.get(name) else {
+        return Ok(None);
+    };
+    let bytes = value
+        .as_str()
+        .ok_or(Error::InvalidJwk(name))
+        .and_then(|text| base64::decode_url(text).map_err(|_| Error::InvalidJwk(name)))?;
+    match bytes.as_slice() {
+        [] | [0, _, ..] => Err(Error::InvalidJwk(name)),
+        bytes => Ok(Some(BigUint::from_bytes_be(bytes))),
+    }
+}
+
+/// Reads a required integer member.
+fn read_integer(object: &Value, name: &'static str) -> Result<BigUint, Error> {
+    read_optional_integer(object, name)?.ok_or(Error::InvalidJwk(name))
+}
+
+/// Reads the public members and checks that `n` is odd and greater than 2 and `e` greater than 1.
+fn read_public_key(object: &Value) -> Result<PublicKey, Error> {
+    let n = read_integer(object, "n")?;
+    if !n.bit(0) || n <= BigUint::from(2u8) {
+        return Err(Error::InvalidJwk("n"));
+    }
+    let e = read_integer(object, "e")?;
+    if e <= BigUint::one() {
+        return Err(Error::InvalidJwk("e"));
+    }
+    Ok(PublicKey { n, e })
+}
+
+/// Reads an optional prime factor, which must be greater than 1.
+fn read_optional_prime(object: &Value, name: &'static str) -> Result<Option<BigUint>, Error> {
+    match read_optional_integer(object, name)? {
+        Some(prime) if prime <= BigUint::one() => Err(Error::InvalidJwk(name)),
+        prime => Ok(prime),
+    }
+}
+
+impl PublicKey {
+    /// The `to_jwk` function encodes the key as a JSON Web Key (RFC 7517, RFC 7518 §6.3.1).
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the JSON text holding `kty`, `n` and `e`.
+    pub fn to_jwk(&self) -> String {
+        encode_members(self.jwk_members())
+    }
+
+    /// The `from_jwk` function decodes a JSON Web Key. Private members are ignored, so the public half
+    /// of a private JWK can be loaded as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk` - The JSON text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or `Error::InvalidJwk` if the JSON is malformed, the key
+    ///   is not an RSA key, or `n` or `e` is missing or out of range.
+    pub fn from_jwk(jwk: &str) -> Result<Self, Error> {
+        read_public_key(&parse_object(jwk)?)
+    }
+
+    /// The `jwk_thumbprint` function calculates the JWK thumbprint of the key (RFC 7638): the SHA-256
+    /// digest of the required members in lexicographic order without whitespace.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the Base64url encoded thumbprint.
+    pub fn jwk_thumbprint(&self) -> String {
+        // The required members of an RSA key are exactly the public ones
+        let canonical = encode_members(self.jwk_members());
+        base64::encode_url(&sha256(canonical.as_bytes()))
+    }
+
+    fn jwk_members(&self) -> Vec<(&'static str, String)> {
+        vec![("kty", KEY_TYPE.to_string()), ("n", encode_integer(&self.n)), ("e", encode_integer(&self.e))]
+    }
+}
+
+impl RSA {
+    /// The `to_jwk` function encodes the key pair as a private JSON Web Key (RFC 7518 §6.3.2). The CRT
+    /// members `p`, `q`, `dp`, `dq` and `qi` are only written when the private key holds the primes.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the JSON text.
+    pub fn to_jwk(&self) -> String {
+        let private_key = self.private_key();
+        let mut members = self.public_key.jwk_members();
+        members.push(("d", encode_integer(private_key.d())));
+
+        if let (Some((p, q)), Some((dp, dq, qinv))) = (private_key.primes(), private_key.crt_values()) {
+            for (name, value) in [("p", p), ("q", q), ("dp", dp), ("dq", dq), ("qi", qinv)] {
+                members.push((name, encode_integer(value)));
+            }
+        }
+
+        encode_members(members)
+    }
+
+    /// The `from_jwk` function decodes a private JSON Web Key. When `p` and `q` are present the CRT
+    /// values are recomputed from them; otherwise the key decrypts without CRT. The key is not
+    /// validated, call `validate` to check it.
+    ///
+    /// # Arguments
+    ///
+    /// * `jwk` - The JSON text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, `Error::InvalidJwk` if the JSON is malformed, `d`
+    ///   is missing, a member is out of range or the members do not form a key, or
+    ///   `Error::UnsupportedAlgorithm` for multi-prime keys.
+    pub fn from_jwk(jwk: &str) -> Result<Self, Error> {
+        let object = parse_object(jwk)?;
+        if object.get("oth").is_some() {
+            return Err(Error::UnsupportedAlgorithm);
+        }
+
+        let public_key = read_public_key(&object)?;
+        let d = read_integer(&object, "d")?;
+
+        let private_key = match (read_optional_prime(&object, "p")?, read_optional_prime(&object, "q")?) {
+            (Some(p), Some(q)) => {
+                let private_key =
+                    PrivateKey::from_primes(p, q, d).map_err(|_| Error::InvalidJwk("p and q are equal or not coprime"))?;
+                if private_key.n() != &public_key.n {
+                    return Err(Error::InvalidJwk("n is not the product of p and q"));
+                }
+                private_key
+            }
+            (None, None) => PrivateKey::new(public_key.n.clone(), d),
+            (Some(_), None) => return Err(Error::InvalidJwk("q")),
+            (None, Some(_)) => return Err(Error::InvalidJwk("p")),
+        };
+
+        let private_key = private_key
+            .with_blinding(public_key.e.clone())
+            .map_err(|_| Error::InvalidJwk("e does not match d"))?;
+        Ok(RSA::from_keys(public_key, private_key))
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::path::Path;
+
+use crate::Error;
+use super::encryption::PublicKey;
+use super::keys::RSA;
+use super::{pbes2, pem, pkcs1, pkcs8};
+
+/// The Key enum holds either half of a key pair loaded from a file.
+pub enum Key {
+    /// A public key.
+    Public(PublicKey),
+    /// A full key pair.
+    Private(RSA),
+}
+
+impl Key {
+    /// Returns the public key, which both variants hold.
+    pub fn public_key(&self) -> &PublicKey {
+        match self {
+            Key::Public(public_key) => public_key,
+            Key::Private(rsa) => &rsa.public_key,
+        }
+    }
+
+    /// The `from_pem` function decodes a key from PEM text, choosing the format from the label:
+    /// `RSA PUBLIC KEY`, `PUBLIC KEY`, `RSA PRIVATE KEY` or `PRIVATE KEY`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Key, Error>` - Returns the key, `Error::PasswordRequired` for an `ENCRYPTED PRIVATE KEY`,
+    ///   or an error if the label is unknown or the key is malformed.
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        let (label, der) = pem::decode(pem)?;
+        match label.as_str() {
+            pkcs1::PUBLIC_KEY_LABEL => Ok(Key::Public(PublicKey::from_pkcs1_der(&der)?)),
+            pkcs8::PUBLIC_KEY_LABEL => Ok(Key::Public(PublicKey::from_spki_der(&der)?)),
+            pkcs1::PRIVATE_KEY_LABEL => Ok(Key::Private(RSA::from_pkcs1_der(&der)?)),
+            pkcs8::PRIVATE_KEY_LABEL => Ok(Key::Private(RSA::from_pkcs8_der(&der)?)),
+            pbes2::ENCRYPTED_PRIVATE_KEY_LABEL => Err(Error::PasswordRequired),
+            _ => Err(Error::InvalidPem),
+        }
+    }
+
+    /// The `from_der` function decodes a key from DER bytes, trying SubjectPublicKeyInfo, PKCS#8,
+    /// PKCS#1 `RSAPrivateKey` and PKCS#1 `RSAPublicKey` in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The DER bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Key, Error>` - Returns the key, or `Error::InvalidDer` if no format matches.
+    pub fn from_der(der: &[u8]) -> Result<Self, Error> {
+        PublicKey::from_spki_der(der)
+            .map(Key::Public)
+            .or_else(|_| RSA::from_pkcs8_der(der).map(Key::Private))
+            .or_else(|_| RSA::from_pkcs1_der(der).map(Key::Private))
+            .or_else(|_| PublicKey::from_pkcs1_der(der).map(Key::Public))
+            .map_err(|_| Error::InvalidDer("not a known key format"))
+    }
+}
+
+/// The `load_key` function reads a key from a PEM or DER file, detecting the format from its content.
+///
+/// # Arguments
+///
+/// * `path` - The path of the key file.
+///
+/// # Returns
+///
+/// * `Result<Key, Error>` - Returns the key, or an error if the file cannot be read or holds no known key format.
+///
+/// # Example
+///
+/// ```no_run
+/// use sop_kode::rsa::load_key;
+///
+/// let key = load_key("id_rsa.pem").expect("Failed to load key");
+/// println!("{} bit key", key.public_key().n.bits());
+/// ```
+pub fn load_key<P: AsRef<Path>>(path: P) -> Result<Key, Error> {
+    let bytes = fs::read(path).map_err(|error| Error::Io(error.kind()))?;
+    match std::str::from_utf8(&bytes) {
+        Ok(text) if text.contains("-----BEGIN ") => Key::from_pem(text),
+        _ => Key::from_der(&bytes),
+    }
+}
@@ -1,10 +1,20 @@
-use super::generate_prime;
-use super::calculate_totient;
-use super::mod_inverse;
+use super::{calculate_carmichael, calculate_totient};
+use super::{miller_rabin, mod_inverse};
 use super::encryption::{PrivateKey, PublicKey};
+use super::validation::{ValidationFailure, ValidationReport};
+use super::builder::RsaBuilder;
+use super::container::Ciphertext;
+use super::stream::Decryptor;
+use crate::Error;
 
 use num_bigint::{BigUint, ToBigInt};
-use super::chunk_message;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+use rayon::prelude::*;
+
+/// The number of Miller-Rabin rounds used when validating the primes of a key. The bases are random, so a
+/// composite crafted to pass fixed bases still fails with a probability of at least 1 - 4^-25.
+const VALIDATION_MR_ROUNDS: usize = 25;
 
 /// The RSA struct represents an RSA encryption/decryption system.
 pub struct RSA {
@@ -13,38 +23,159 @@ pub struct RSA {
 }
 
 impl RSA {
-    /// Constructs a new RSA system with the given number of bits.
+    /// Constructs a new RSA system with the given number of bits. This is `RsaBuilder::new().bits(bits).build()`,
+    /// so the key uses e = 65537 and primes drawn from `thread_rng`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The number of bits for the RSA system.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - Returns an RSA system whose modulus has exactly `bits` bits, or an error
+    ///   if `bits` is odd or too small.
+    pub fn new(bits: usize) -> Result<Self, Error> {
+        RsaBuilder::new().bits(bits).build()
+    }
+
+    /// Constructs a new RSA system with the given number of bits, drawing the primes from the given
+    /// random number generator. Using a seeded generator always yields the same key pair.
     ///
     /// # Arguments
     ///
     /// * `bits` - The number of bits for the RSA system.
+    /// * `rng` - The random number generator used to draw the primes.
     ///
     /// # Returns
     ///
-    /// * `Option<Self>` - Returns an RSA system if successful, or None if the generated primes are equal.
-    pub fn new(bits: usize) -> Option<Self> {
-        let (p, q) = rayon::join(|| generate_prime(bits / 2), || generate_prime(bits / 2));
-        
+    /// * `Result<Self, Error>` - Returns an RSA system whose modulus has exactly `bits` bits, or an error
+    ///   if `bits` is odd or too small.
+    pub fn new_with_rng<R: RngCore + CryptoRng + ?Sized>(bits: usize, rng: &mut R) -> Result<Self, Error> {
+        RsaBuilder::new().bits(bits).rng(rng).build()
+    }
 
+    /// Constructs a new RSA system from two primes and a public exponent.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The first prime factor of the modulus.
+    /// * `q` - The second prime factor of the modulus.
+    /// * `e` - The public exponent.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - Returns an RSA system, or an error if the primes are equal or smaller
+    ///   than 2, `e` has no inverse modulo the totient or the pairwise consistency test fails.
+    pub fn from_primes(p: BigUint, q: BigUint, e: BigUint) -> Result<Self, Error> {
         if p == q {
-            return None;
+            return Err(Error::PrimeCollision);
+        }
+        if p <= BigUint::one() || q <= BigUint::one() {
+            return Err(Error::InvalidPrimeFactors);
         }
 
-
-        let n = p.clone() * q.clone();
+        let n = &p * &q;
         let phi = calculate_totient(&p, &q);
 
-        let e = BigUint::from(65537u64); // Commonly used public exponent
+        let d = mod_inverse(e.to_bigint().unwrap(), phi.to_bigint().unwrap());
+        if d.is_zero() {
+            return Err(Error::NonInvertibleExponent);
+        }
+
+        let private_key = PrivateKey::from_primes(p, q, d.to_biguint().unwrap())?.with_blinding(e.clone())?;
+        let rsa = Self {
+            public_key: PublicKey { n, e },
+            private_key,
+        };
 
-        let d = mod_inverse(e.clone().to_bigint().unwrap(), phi.to_bigint().unwrap());
+        if !rsa.pairwise_consistency() {
+            return Err(Error::PairwiseConsistency);
+        }
 
-        Some(Self {
-            public_key: PublicKey { n: n.clone(), e },
-            private_key: PrivateKey::new(n, d.to_biguint().unwrap()),
-        })
+        Ok(rsa)
     }
 
-    /// Encrypts a message using the given public key.
+    /// Constructs a new RSA system from an existing public and private key without checking them.
+    /// Call `validate` to check that the two keys belong together. The private key is kept as it is,
+    /// so it only blinds if it was made `with_blinding`.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key` - The public key of the RSA system.
+    /// * `private_key` - The private key of the RSA system.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - Returns an RSA system holding the two keys.
+    pub fn from_keys(public_key: PublicKey, private_key: PrivateKey) -> Self {
+        Self { public_key, private_key }
+    }
+
+    /// The `validate` function checks that the key pair is sound. On top of the checks done by
+    /// `PublicKey::validate`, it checks that `p` and `q` are probable primes of half the size of `n`,
+    /// that `n = p * q`, that `e * d ≡ 1 (mod λ(n))` and that a test message survives a round trip.
+    /// A private key without its prime factors is reported as `MissingPrimeFactors`.
+    ///
+    /// # Returns
+    ///
+    /// * `ValidationReport` - Returns a report listing every check the key pair failed.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = self.public_key.validate();
+        let n = &self.public_key.n;
+
+        let mut modulus_mismatch = self.private_key.n() != n;
+
+        if let Some((p, q)) = self.private_key.primes() {
+            if !miller_rabin(p, VALIDATION_MR_ROUNDS) {
+                report.fail(ValidationFailure::PNotPrime);
+            }
+            if !miller_rabin(q, VALIDATION_MR_ROUNDS) {
+                report.fail(ValidationFailure::QNotPrime);
+            }
+            modulus_mismatch |= &(p * q) != n;
+            if p.bits() + q.bits() != n.bits() || p.bits().abs_diff(q.bits()) > 1 {
+                report.fail(ValidationFailure::PrimeSizeMismatch { n_bits: n.bits(), p_bits: p.bits(), q_bits: q.bits() });
+            }
+
+            let lambda = calculate_carmichael(p, q);
+            if lambda.is_zero() || !((&self.public_key.e * self.private_key.d()) % &lambda).is_one() {
+                report.fail(ValidationFailure::ExponentMismatch);
+            }
+        } else {
+            report.fail(ValidationFailure::MissingPrimeFactors);
+        }
+
+        if modulus_mismatch {
+            report.fail(ValidationFailure::ModulusMismatch);
+        }
+
+        if !self.pairwise_consistency() {
+            report.fail(ValidationFailure::PairwiseConsistency);
+        }
+
+        report
+    }
+
+    /// Encrypts a test message with the public key and checks that the private key decrypts it back.
+    fn pairwise_consistency(&self) -> bool {
+        let message = &self.public_key.n >> 1u32;
+        match self.public_key.encrypt(&message, &self.public_key) {
+            Ok(ciphertext) => ciphertext != message && self.private_key.decrypt(&ciphertext) == message,
+            Err(_) => false,
+        }
+    }
+
+    /// Returns a reference to the private key of the RSA system.
+    ///
+    /// # Returns
+    ///
+    /// * `&PrivateKey` - Returns the private key.
+    pub fn private_key(&self) -> &PrivateKey {
+        &self.private_key
+    }
+
+    /// Encrypts a message using the given public key. The chunk size comes from `public_key`, so this
+    /// is the same as calling `public_key.encrypt_message(message)`.
     ///
     /// # Arguments
     ///
@@ -53,51 +184,122 @@ impl RSA {
     ///
     /// # Returns
     ///
-    /// * `Vec<BigUint>` - Returns the encrypted message as a vector of BigUint.
-    pub fn encrypt_message(&self, message: &str, public_key: PublicKey) -> Vec<BigUint> {
+    /// * `Result<Ciphertext, Error>` - Returns the ciphertext container, or `Error::KeyTooSmall` if the
+    ///   modulus of `public_key` cannot hold a chunk.
+    pub fn encrypt_message(&self, message: &str, public_key: PublicKey) -> Result<Ciphertext, Error> {
+        public_key.encrypt_message(message)
+    }
 
-        // Calculate the chunk size
-        let chunk_size = &self.private_key.get_chunk_size();
+    /// Decrypts an encrypted message.
+    ///
+    /// # Arguments
+    ///
+    /// * `ciphertext` - The ciphertext container, for example parsed with `Ciphertext::from_bytes`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the decrypted message as a string, or an error as described for
+    ///   `decrypt_bytes`, or `Error::InvalidUtf8` if the decrypted bytes are not valid UTF-8.
+    pub fn decrypt_message(&self, ciphertext: &Ciphertext) -> Result<String, Error> {
+        String::from_utf8(self.decrypt_bytes(ciphertext)?).map_err(|_| Error::InvalidUtf8)
+    }
 
-        // Convert the string to chunks of bytes
-        let chunks = chunk_message(message, *chunk_size);
+    /// Decrypts bytes encrypted with `PublicKey::encrypt_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ciphertext` - The ciphertext container.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the decrypted bytes, `Error::WrongKey` if the ciphertext was
+    ///   encrypted to another key, `Error::InvalidCiphertext` if the blocks do not fit this key,
+    ///   `Error::InvalidPadding` if a block is not correctly padded, or `Error::InvalidFraming` if the
+    ///   decrypted length does not match. Either of the last two means it was tampered with.
+    ///   `Error::LabelMismatch` means it was bound to an OAEP label; see `decrypt_bytes_with_label`.
+    pub fn decrypt_bytes(&self, ciphertext: &Ciphertext) -> Result<Vec<u8>, Error> {
+        self.decrypt_bytes_with_label(ciphertext, &[])
+    }
+
+    /// Decrypts bytes like `decrypt_bytes`, checking the OAEP label the message was bound to.
+    ///
+    /// # Arguments
+    ///
+    /// * `ciphertext` - The ciphertext container.
+    /// * `label` - The label the message was encrypted with, empty if there was none.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the decrypted bytes, `Error::LabelMismatch` if the container
+    ///   records a label but none was given or the other way round, `Error::InvalidPadding` if the label
+    ///   differs from the one the message was encrypted with, or an error as described for `decrypt_bytes`.
+    pub fn decrypt_bytes_with_label(&self, ciphertext: &Ciphertext, label: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut decryptor = self.decryptor(ciphertext, label)?;
 
-        // Convert each chunk of bytes to a BigUint and encrypt it
-        let encrypted_chunks: Vec<BigUint> = chunks.into_iter()
-            .map(|chunk| {
-                let chunk_biguint = BigUint::from_bytes_be(&chunk);
-                public_key.encrypt(&chunk_biguint, &public_key)
-            })
-            .collect();
+        // Sized from the blocks rather than the stored length, which may have been tampered with
+        let mut message = Vec::with_capacity(ciphertext.blocks().len() * ciphertext.block_size());
+        for block in ciphertext.blocks() {
+            decryptor.update(block, &mut message)?;
+        }
+        decryptor.finish(ciphertext.blocks().len() as u64, ciphertext.len(), &mut message)?;
+        Ok(message)
+    }
 
-        encrypted_chunks
+    /// Decrypts an encrypted message like `decrypt_message`, but decrypts the blocks in parallel on the
+    /// rayon thread pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `ciphertext` - The ciphertext container.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the same result as `decrypt_message`.
+    pub fn par_decrypt_message(&self, ciphertext: &Ciphertext) -> Result<String, Error> {
+        String::from_utf8(self.par_decrypt_bytes(ciphertext)?).map_err(|_| Error::InvalidUtf8)
     }
 
-    /// Decrypts an encrypted message.
+    /// Decrypts bytes like `decrypt_bytes`, but decrypts the blocks in parallel on the rayon thread pool.
+    /// Only the independent `modpow` work is parallel; the chunks are reassembled in order.
     ///
     /// # Arguments
     ///
-    /// * `encrypted_message` - The encrypted message to decrypt.
+    /// * `ciphertext` - The ciphertext container.
     ///
     /// # Returns
     ///
-    /// * `String` - Returns the decrypted message as a string.
-    pub fn decrypt_message(&self, encrypted_message: Vec<BigUint>) -> String {
-        // Decrypt each chunk separately
-        let decrypted_chunks: Vec<Vec<u8>> = encrypted_message.into_iter()
-            .map(|chunk| {
-                let decrypted_chunk = self.private_key.decrypt(&chunk);
-                decrypted_chunk.to_bytes_be()
-            })
-            .collect();
+    /// * `Result<Vec<u8>, Error>` - Returns the same result as `decrypt_bytes`.
+    pub fn par_decrypt_bytes(&self, ciphertext: &Ciphertext) -> Result<Vec<u8>, Error> {
+        self.par_decrypt_bytes_with_label(ciphertext, &[])
+    }
 
-        // Concatenate the decrypted chunks together to recover the original message
-        let decrypted_message: Vec<u8> = decrypted_chunks.into_iter().flatten().collect();
-        let decrypted_message = String::from_utf8(decrypted_message).unwrap();
+    /// Decrypts bytes like `decrypt_bytes_with_label`, but decrypts the blocks in parallel on the rayon
+    /// thread pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `ciphertext` - The ciphertext container.
+    /// * `label` - The label the message was encrypted with, empty if there was none.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the same result as `decrypt_bytes_with_label`.
+    pub fn par_decrypt_bytes_with_label(&self, ciphertext: &Ciphertext, label: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut decryptor = self.decryptor(ciphertext, label)?;
+        let chunks = ciphertext.blocks()
+            .par_iter()
+            .map(|block| decryptor.decrypt_block(block))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Remove any trailing null characters from the decrypted message
-        let decrypted_message = decrypted_message.trim_end_matches('\0');
+        let mut message = Vec::with_capacity(chunks.len() * ciphertext.block_size());
+        for chunk in &chunks {
+            decryptor.push_chunk(chunk, &mut message)?;
+        }
+        decryptor.finish(ciphertext.blocks().len() as u64, ciphertext.len(), &mut message)?;
+        Ok(message)
+    }
 
-        decrypted_message.to_string()
+    fn decryptor(&self, ciphertext: &Ciphertext, label: &[u8]) -> Result<Decryptor<'_>, Error> {
+        Decryptor::new(self, &ciphertext.header(), label)
     }
 }
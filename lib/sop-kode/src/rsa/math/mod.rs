@@ -1,5 +1,12 @@
-use num_bigint::{BigInt, BigUint, Sign};
+use num_bigint::{BigInt, BigUint, RandBigInt, Sign, ToBigInt};
 use num_traits::{One, Zero, Signed, ToPrimitive};
+use rand::{CryptoRng, RngCore};
+
+use crate::Error;
+
+pub mod montgomery;
+
+pub use montgomery::{ct_modpow, Montgomery};
 
 
 
@@ -99,6 +106,7 @@ pub fn binary_extended_gcd(a: &BigInt, b: &BigInt, x: &mut BigInt, y: &mut BigIn
 ///
 /// This function is deprecated and `binary_extended_gcd` should be used instead.
 #[deprecated(note = "Use `binary_extended_gcd` instead.")]
+#[allow(dead_code)]
 #[inline]
 fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
     if a == 0 {
@@ -125,6 +133,24 @@ pub fn calculate_totient(p: &BigUint, q: &BigUint) -> BigUint {
     (p - BigUint::one()) * (q - BigUint::one())
 }
 
+/// The `calculate_carmichael` function calculates the Carmichael function λ for the product of two prime numbers `p` and `q`.
+/// λ(n) is the smallest exponent such that a^λ(n) ≡ 1 (mod n) for every `a` coprime to `n`, and it equals lcm(p - 1, q - 1).
+///
+/// # Arguments
+///
+/// * `p` - A reference to a BigUint that represents the first prime number.
+/// * `q` - A reference to a BigUint that represents the second prime number.
+///
+/// # Returns
+///
+/// * `BigUint` - The value of the Carmichael function for `p * q`.
+pub fn calculate_carmichael(p: &BigUint, q: &BigUint) -> BigUint {
+    let p_minus_one = BigInt::from(p - BigUint::one());
+    let q_minus_one = BigInt::from(q - BigUint::one());
+    let (mut x, mut y) = (BigInt::zero(), BigInt::zero());
+    let gcd = binary_extended_gcd(&p_minus_one, &q_minus_one, &mut x, &mut y);
+    ((p_minus_one * q_minus_one) / gcd).to_biguint().unwrap()
+}
 
 /// The `mod_inverse` function calculates the modular multiplicative inverse of a number.
 /// The modular multiplicative inverse of `a` modulo `m` is an integer `x` such that
@@ -157,3 +183,20 @@ pub fn mod_inverse(a: BigInt, m: BigInt) -> BigInt {
         result
     }
 }
+
+/// Draws a random `r` in `[2, n)` that is invertible modulo `n`, and returns it with its inverse, or
+/// `Error::KeyTooSmall` if `n` is smaller than 3 and there is nothing to draw from.
+pub(crate) fn random_unit<R: RngCore + CryptoRng + ?Sized>(n: &BigUint, rng: &mut R) -> Result<(BigUint, BigUint), Error> {
+    let two = BigUint::from(2u8);
+    if n <= &two {
+        return Err(Error::KeyTooSmall);
+    }
+    loop {
+        let r = rng.gen_biguint_range(&two, n);
+        let inverse = mod_inverse(r.to_bigint().unwrap(), n.to_bigint().unwrap()).to_biguint().unwrap();
+        // A factor that shares a prime with n has no inverse, so draw again
+        if !inverse.is_zero() {
+            return Ok((r, inverse));
+        }
+    }
+}
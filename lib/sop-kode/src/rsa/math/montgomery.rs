@@ -0,0 +1,213 @@
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// The number of exponent bits consumed per table lookup.
+const WINDOW_BITS: usize = 4;
+
+/// The number of precomputed powers, `base^0` to `base^15`.
+const TABLE_SIZE: usize = 1 << WINDOW_BITS;
+
+/// Returns all ones if `a == b` and zero otherwise, without branching.
+fn ct_eq_mask(a: usize, b: usize) -> u64 {
+    let x = (a ^ b) as u64;
+    // The top bit of x | -x is set exactly when x is nonzero
+    ((x | x.wrapping_neg()) >> 63).wrapping_sub(1)
+}
+
+/// Splits a value into exactly `len` little-endian limbs. The value must fit.
+fn to_limbs(value: &BigUint, len: usize) -> Vec<u64> {
+    let mut limbs = value.to_u64_digits();
+    debug_assert!(limbs.len() <= len);
+    limbs.resize(len, 0);
+    limbs
+}
+
+/// Joins little-endian limbs into a value.
+fn from_limbs(limbs: &[u64]) -> BigUint {
+    let bytes: Vec<u8> = limbs.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+    BigUint::from_bytes_le(&bytes)
+}
+
+/// The `Montgomery` struct holds an odd modulus `n` in fixed-width limbs together with the constants
+/// needed to multiply in Montgomery form, where `x` is represented by `x * R mod n` for `R = 2^(64 * limbs)`.
+/// Every number modulo `n` is held in the same number of limbs, so which operations run, and on how
+/// many limbs, depends only on the size of the modulus.
+#[derive(Clone, Debug)]
+pub struct Montgomery {
+    modulus: BigUint,
+    limbs: Vec<u64>,
+    /// `-n^-1 mod 2^64`.
+    n0_inv: u64,
+    /// `R^2 mod n`, used to move values into Montgomery form.
+    r_squared: Vec<u64>,
+}
+
+impl Montgomery {
+    /// Constructs the Montgomery constants for a modulus.
+    ///
+    /// # Arguments
+    ///
+    /// * `modulus` - The modulus `n`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Montgomery>` - Returns the constants, or `None` if the modulus is even, as `R` then has
+    ///   no inverse modulo `n`.
+    pub fn new(modulus: &BigUint) -> Option<Self> {
+        if !modulus.bit(0) {
+            return None;
+        }
+
+        let len = modulus.to_u64_digits().len();
+        let limbs = to_limbs(modulus, len);
+
+        // Newton's iteration doubles the correct low bits of n^-1 each step, from 1 bit to 64
+        let mut inverse = 1u64;
+        for _ in 0..6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inverse)));
+        }
+
+        let r_squared = (BigUint::one() << (128 * len)) % modulus;
+        Some(Self {
+            modulus: modulus.clone(),
+            limbs,
+            n0_inv: inverse.wrapping_neg(),
+            r_squared: to_limbs(&r_squared, len),
+        })
+    }
+
+    /// Returns the modulus `n`.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Computes `a * b * R^-1 mod n` for `a, b < n` with the coarsely integrated operand scanning (CIOS)
+    /// method. The loops only depend on the number of limbs, and the final subtraction is masked in.
+    fn multiply(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let s = self.limbs.len();
+        let n = &self.limbs;
+        let mut t = vec![0u64; s + 2];
+
+        for &b_i in b {
+            // t += a * b_i
+            let mut carry = 0u64;
+            for j in 0..s {
+                let sum = t[j] as u128 + a[j] as u128 * b_i as u128 + carry as u128;
+                t[j] = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let sum = t[s] as u128 + carry as u128;
+            t[s] = sum as u64;
+            t[s + 1] = (sum >> 64) as u64;
+
+            // t = (t + m * n) / 2^64, where m makes the lowest limb zero
+            let m = t[0].wrapping_mul(self.n0_inv);
+            let sum = t[0] as u128 + m as u128 * n[0] as u128;
+            let mut carry = (sum >> 64) as u64;
+            for j in 1..s {
+                let sum = t[j] as u128 + m as u128 * n[j] as u128 + carry as u128;
+                t[j - 1] = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let sum = t[s] as u128 + carry as u128;
+            t[s - 1] = sum as u64;
+            t[s] = t[s + 1] + (sum >> 64) as u64;
+        }
+
+        // t < 2n, so t - n is the result unless it borrows past the top limb
+        let mut reduced = vec![0u64; s];
+        let mut borrow = 0u64;
+        for j in 0..s {
+            let (difference, first) = t[j].overflowing_sub(n[j]);
+            let (difference, second) = difference.overflowing_sub(borrow);
+            reduced[j] = difference;
+            borrow = (first | second) as u64;
+        }
+        let (_, underflow) = t[s].overflowing_sub(borrow);
+        let keep_reduced = (underflow as u64).wrapping_sub(1);
+        for j in 0..s {
+            reduced[j] = (reduced[j] & keep_reduced) | (t[j] & !keep_reduced);
+        }
+        reduced
+    }
+
+    /// Returns `table[index]`, reading every entry so the index does not show in the memory accesses.
+    fn select(&self, table: &[Vec<u64>], index: usize) -> Vec<u64> {
+        let mut selected = vec![0u64; self.limbs.len()];
+        for (i, entry) in table.iter().enumerate() {
+            let mask = ct_eq_mask(i, index);
+            for (limb, &value) in selected.iter_mut().zip(entry) {
+                *limb |= value & mask;
+            }
+        }
+        selected
+    }
+
+    /// The `modpow` function computes `base^exponent mod n` with fixed 4-bit windows. Every window
+    /// costs four squarings and one multiplication, also when its bits are zero, and the exponent is
+    /// always read to the full width of the modulus.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The base. It is reduced modulo `n` first.
+    /// * `exponent` - The secret exponent.
+    ///
+    /// # Returns
+    ///
+    /// * `BigUint` - Returns `base^exponent mod n`, the same value as `BigUint::modpow`.
+    pub fn modpow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let s = self.limbs.len();
+        let base = to_limbs(&(base % &self.modulus), s);
+
+        // Montgomery form of 1 is R mod n, and of the base is base * R^2 * R^-1
+        let mut one = vec![0u64; s];
+        one[0] = 1;
+        let mut table = Vec::with_capacity(TABLE_SIZE);
+        table.push(self.multiply(&one, &self.r_squared));
+        table.push(self.multiply(&base, &self.r_squared));
+        for i in 2..TABLE_SIZE {
+            let next = self.multiply(&table[i - 1], &table[1]);
+            table.push(next);
+        }
+
+        let exponent_len = exponent.to_u64_digits().len().max(s);
+        let exponent = to_limbs(exponent, exponent_len);
+        let windows = exponent_len * 64 / WINDOW_BITS;
+
+        let mut accumulator = table[0].clone();
+        for window in (0..windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                accumulator = self.multiply(&accumulator, &accumulator);
+            }
+            let bit = window * WINDOW_BITS;
+            let index = (exponent[bit / 64] >> (bit % 64)) as usize & (TABLE_SIZE - 1);
+            accumulator = self.multiply(&accumulator, &self.select(&table, index));
+        }
+
+        // Multiplying by 1 divides out the last R
+        from_limbs(&self.multiply(&accumulator, &one))
+    }
+}
+
+/// The `ct_modpow` function computes `base^exponent mod modulus` in constant time with respect to the
+/// exponent. RSA moduli and primes are odd; an even modulus falls back to `BigUint::modpow`.
+///
+/// `BigUint::modpow` skips work for zero bits and trims leading zero limbs, so its timing depends on
+/// the exponent. Only the exponent is protected here: reducing the base and converting the result back
+/// use `BigUint`, which is fine for RSA as the base is the (blinded) ciphertext.
+///
+/// # Arguments
+///
+/// * `base` - The base.
+/// * `exponent` - The secret exponent.
+/// * `modulus` - The modulus.
+///
+/// # Returns
+///
+/// * `BigUint` - Returns `base^exponent mod modulus`.
+pub fn ct_modpow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    match Montgomery::new(modulus) {
+        Some(montgomery) => montgomery.modpow(base, exponent),
+        None => base.modpow(exponent, modulus),
+    }
+}
@@ -1,11 +1,31 @@
 pub mod keys;
+pub mod builder;
 pub mod encryption;
 pub mod primality;
 pub mod math;
 pub mod utils;
-mod padding;
+pub mod padding;
+pub mod validation;
+pub mod pem;
+pub mod pkcs1;
+pub mod pkcs8;
+pub mod pbes2;
+pub mod key_file;
+pub mod openssh;
+pub mod jwk;
+pub mod container;
+pub mod armor;
+pub mod stream;
+pub mod signature;
+pub mod blind;
 
 pub use keys::{RSA};
-pub use primality::{miller_rabin, generate_prime};
-pub use math::{binary_extended_gcd, mod_inverse, calculate_totient};
-pub use utils::{base_n_to_base10, chunk_message, calculate_chunk_size, estimate_brute_force_time, format_duration};
+pub use key_file::{load_key, Key};
+pub use container::Ciphertext;
+pub use armor::{signature_from_armor, signature_to_armor, Armor, ArmorKind};
+pub use padding::PaddingScheme;
+pub use builder::RsaBuilder;
+pub use validation::{ValidationFailure, ValidationReport};
+pub use primality::{miller_rabin, miller_rabin_with_rng, generate_prime, generate_prime_with};
+pub use math::{binary_extended_gcd, mod_inverse, calculate_totient, calculate_carmichael, ct_modpow};
+pub use utils::{base_n_to_base10, chunk_message, chunk_bytes, frame_bytes, unframe_bytes, framed_len, calculate_chunk_size, estimate_brute_force_time, format_duration};
@@ -0,0 +1,143 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::hash::sha256;
+use crate::Error;
+use crate::base64;
+use super::encryption::PublicKey;
+
+/// The OpenSSH key type name of RSA keys.
+pub const KEY_TYPE: &str = "ssh-rsa";
+
+/// Appends an SSH `string`: a 32-bit big-endian length followed by the bytes (RFC 4251 §5).
+fn write_string(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+/// Appends an SSH `mpint`: a two's complement string without unnecessary leading bytes (RFC 4251 §5).
+fn write_mpint(buffer: &mut Vec<u8>, value: &BigUint) {
+    let mut bytes = if value.is_zero() { Vec::new() } else { value.to_bytes_be() };
+    if bytes.first().is_some_and(|&b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    write_string(buffer, &bytes);
+}
+
+/// Reads an SSH `string` from the front of the buffer.
+fn read_string<'a>(buffer: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    if buffer.len() < 4 {
+        return Err(Error::InvalidOpenSsh);
+    }
+    let (length, rest) = buffer.split_at(4);
+    let length = u32::from_be_bytes(length.try_into().unwrap()) as usize;
+    if length > rest.len() {
+        return Err(Error::InvalidOpenSsh);
+    }
+    let (bytes, rest) = rest.split_at(length);
+    *buffer = rest;
+    Ok(bytes)
+}
+
+/// Reads a non-negative SSH `mpint` from the front of the buffer.
+fn read_mpint(buffer: &mut &[u8]) -> Result<BigUint, Error> {
+    match read_string(buffer)? {
+        [first, ..] if first & 0x80 != 0 => Err(Error::InvalidOpenSsh),
+        [0, second, ..] if second & 0x80 == 0 => Err(Error::InvalidOpenSsh),
+        [0] => Err(Error::InvalidOpenSsh),
+        bytes => Ok(BigUint::from_bytes_be(bytes)),
+    }
+}
+
+impl PublicKey {
+    /// The `to_openssh_blob` function encodes the key in the SSH wire format (RFC 4253 §6.6):
+    /// the string `ssh-rsa` followed by `e` and `n` as mpints.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - Returns the encoded key.
+    pub fn to_openssh_blob(&self) -> Vec<u8> {
+        let mut blob = Vec::new();
+        write_string(&mut blob, KEY_TYPE.as_bytes());
+        write_mpint(&mut blob, &self.e);
+        write_mpint(&mut blob, &self.n);
+        blob
+    }
+
+    /// The `from_openssh_blob` function decodes a key from the SSH wire format.
+    ///
+    /// # Arguments
+    ///
+    /// * `blob` - The encoded key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or `Error::InvalidOpenSsh` if the encoding is malformed,
+    ///   the key is not an RSA key, `e` is smaller than 3 or `n` is even or smaller than 3.
+    pub fn from_openssh_blob(mut blob: &[u8]) -> Result<Self, Error> {
+        if read_string(&mut blob)? != KEY_TYPE.as_bytes() {
+            return Err(Error::InvalidOpenSsh);
+        }
+        let e = read_mpint(&mut blob)?;
+        let n = read_mpint(&mut blob)?;
+        if !blob.is_empty() {
+            return Err(Error::InvalidOpenSsh);
+        }
+        // An odd modulus of at least 3 and an exponent of at least 3 are the least an RSA key can have
+        if e < BigUint::from(3u8) || n < BigUint::from(3u8) || !n.bit(0) {
+            return Err(Error::InvalidOpenSsh);
+        }
+        Ok(PublicKey { n, e })
+    }
+
+    /// The `to_openssh` function formats the key as an OpenSSH public key line, as found in
+    /// `~/.ssh/authorized_keys` and `id_rsa.pub`.
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - The comment after the key, usually `user@host`. Leave it empty for no comment.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the line `ssh-rsa AAAA... comment`.
+    pub fn to_openssh(&self, comment: &str) -> String {
+        let line = format!("{} {}", KEY_TYPE, base64::encode(&self.to_openssh_blob()));
+        if comment.is_empty() {
+            line
+        } else {
+            format!("{} {}", line, comment)
+        }
+    }
+
+    /// The `from_openssh` function parses an OpenSSH public key line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line `ssh-rsa AAAA... comment`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(PublicKey, String), Error>` - Returns the key and its comment, or `Error::InvalidOpenSsh`
+    ///   if the line is malformed or the key is not an RSA key.
+    pub fn from_openssh(line: &str) -> Result<(Self, String), Error> {
+        let mut fields = line.trim().splitn(3, char::is_whitespace);
+        if fields.next() != Some(KEY_TYPE) {
+            return Err(Error::InvalidOpenSsh);
+        }
+        let blob = base64::decode(fields.next().ok_or(Error::InvalidOpenSsh)?).map_err(|_| Error::InvalidOpenSsh)?;
+        let comment = fields.next().unwrap_or("").trim().to_string();
+
+        Ok((Self::from_openssh_blob(&blob)?, comment))
+    }
+
+    /// The `openssh_fingerprint` function computes the fingerprint shown by `ssh-keygen -l`:
+    /// `SHA256:` followed by the unpadded Base64 of the SHA-256 digest of the wire format.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the fingerprint.
+    pub fn openssh_fingerprint(&self) -> String {
+        let digest = sha256(&self.to_openssh_blob());
+        format!("SHA256:{}", base64::encode(&digest).trim_end_matches('='))
+    }
+}
@@ -1,33 +1,381 @@
-use rand::Rng;
+use rand::{CryptoRng, Rng, RngCore};
 use std::iter;
 use num_bigint::BigUint;
+use crate::hash::HashAlg;
+use crate::{calculate_chunk_size, Error};
 
-/// The `pkcs1_pad` function is used to apply PKCS1 padding to a message.
+/// The number of bytes RSAES-PKCS1-v1_5 adds to every message: `0x00`, `0x02`, at least eight bytes
+/// of random padding and the `0x00` separator.
+pub const PKCS1_OVERHEAD: usize = 11;
+
+/// Returns `0xff` if `a` equals `b` and `0x00` otherwise, without branching on the values.
+fn ct_eq(a: u8, b: u8) -> u8 {
+    (((a ^ b) as u16).wrapping_sub(1) >> 8) as u8
+}
+
+/// Returns `usize::MAX` if `mask` is `0xff` and `0` if it is `0x00`.
+fn ct_widen(mask: u8) -> usize {
+    (mask as usize & 1).wrapping_neg()
+}
+
+/// The `pkcs1_pad` function encodes a message for RSAES-PKCS1-v1_5 encryption (RFC 8017 §7.2.1):
+/// `0x00 || 0x02 || PS || 0x00 || M`, where `PS` is at least eight random nonzero bytes filling the
+/// encoding to the width of the modulus.
+///
+/// # Arguments
+///
+/// * `message` - The message to be padded.
+/// * `k` - The byte length of the modulus.
+/// * `rng` - The random number generator used to draw the padding bytes.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the `k` byte encoding, or `Error::MessageTooLong` if the message
+///   is longer than `k - 11` bytes.
+pub fn pkcs1_pad<R: RngCore + CryptoRng + ?Sized>(message: &[u8], k: usize, rng: &mut R) -> Result<Vec<u8>, Error> {
+    if message.len() + PKCS1_OVERHEAD > k {
+        return Err(Error::MessageTooLong);
+    }
+
+    let mut padded_message = Vec::with_capacity(k);
+    padded_message.extend_from_slice(&[0x00, 0x02]);
+    padded_message.extend(iter::repeat_with(|| rng.gen_range(1..=u8::MAX)).take(k - message.len() - 3));
+    padded_message.push(0x00);
+    padded_message.extend_from_slice(message);
+    Ok(padded_message)
+}
+
+/// The `pkcs1_unpad` function removes RSAES-PKCS1-v1_5 padding (RFC 8017 §7.2.2). Every byte is
+/// inspected and the checks are combined before the single decision, so the running time does not
+/// reveal which check failed.
 ///
 /// # Arguments
 ///
-/// * `message` - A BigUint value representing the message to be padded.
+/// * `padded_message` - The decrypted block as exactly `k` bytes, leading zeros included.
 ///
 /// # Returns
 ///
-/// * `Vec<u8>` - Returns the padded message.
-pub fn pkcs1_pad(message: &BigUint) -> Vec<u8> {
-    let mut rng = rand::thread_rng();
-    let mut padded_message: Vec<u8> = iter::repeat_with(|| rng.gen()).take(8).collect();
-    padded_message.extend_from_slice(&message.to_bytes_be());
-    padded_message
+/// * `Result<&[u8], Error>` - Returns the message, or `Error::InvalidPadding` if the encoding does not
+///   start with `0x00 0x02`, the padding is shorter than eight bytes or the separator is missing.
+pub fn pkcs1_unpad(padded_message: &[u8]) -> Result<&[u8], Error> {
+    if padded_message.len() < PKCS1_OVERHEAD {
+        return Err(Error::InvalidPadding);
+    }
+
+    let mut valid = ct_eq(padded_message[0], 0x00) & ct_eq(padded_message[1], 0x02);
+    let mut looking = 0xffu8;
+    let mut separator = 0usize;
+    for (i, &byte) in padded_message.iter().enumerate().skip(2) {
+        let found = looking & ct_eq(byte, 0x00);
+        separator |= i & ct_widen(found);
+        looking &= !found;
+    }
+    valid &= !looking;
+    // The padding is at least eight bytes, so the separator is at index ten or later
+    valid &= ((separator.wrapping_sub(PKCS1_OVERHEAD - 1) >> (usize::BITS - 1)) as u8).wrapping_sub(1);
+
+    if valid == 0 {
+        return Err(Error::InvalidPadding);
+    }
+    Ok(&padded_message[separator + 1..])
 }
 
-/// The `pkcs1_unpad` function is used to remove PKCS1 padding from a message.
+/// The `mgf1` function is the MGF1 mask generation function (RFC 8017 §B.2.1): the digests of the
+/// seed followed by a 32-bit big-endian counter, concatenated.
 ///
 /// # Arguments
 ///
-/// * `padded_message` - A BigUint value representing the padded message.
+/// * `hash` - The hash function the mask is built from.
+/// * `seed` - The seed the mask is generated from.
+/// * `length` - The length of the mask in bytes.
 ///
 /// # Returns
 ///
-/// * `BigUint` - Returns the unpadded message.
-pub fn pkcs1_unpad(padded_message: &BigUint) -> BigUint {
-    let unpadded_message = BigUint::from_bytes_be(&padded_message.to_bytes_be()[8..]);
-    unpadded_message
+/// * `Vec<u8>` - Returns the mask.
+pub fn mgf1(hash: HashAlg, seed: &[u8], length: usize) -> Vec<u8> {
+    let mut mask = Vec::with_capacity(length.next_multiple_of(hash.digest_size()));
+    let mut input = seed.to_vec();
+    let mut counter = 0u32;
+    while mask.len() < length {
+        input.truncate(seed.len());
+        input.extend_from_slice(&counter.to_be_bytes());
+        mask.extend_from_slice(&hash.digest(&input));
+        counter += 1;
+    }
+    mask.truncate(length);
+    mask
+}
+
+/// XORs the mask into the bytes in place.
+pub(crate) fn xor_in_place(bytes: &mut [u8], mask: &[u8]) {
+    bytes.iter_mut().zip(mask).for_each(|(byte, mask)| *byte ^= mask);
+}
+
+/// The `oaep_overhead` function returns the number of bytes RSAES-OAEP with the given hash adds to
+/// every message: `0x00`, the masked seed, the label hash and the `0x01` separator.
+///
+/// # Arguments
+///
+/// * `hash` - The hash function used for the label hash and MGF1.
+///
+/// # Returns
+///
+/// * `usize` - Returns `2 * hLen + 2`.
+pub fn oaep_overhead(hash: HashAlg) -> usize {
+    2 * hash.digest_size() + 2
+}
+
+/// The `oaep_pad` function encodes a message for RSAES-OAEP encryption with the given hash function
+/// and MGF1 (RFC 8017 §7.1.1): `0x00 || maskedSeed || maskedDB`, where `DB = lHash || PS || 0x01 || M`.
+///
+/// # Arguments
+///
+/// * `message` - The message to be padded.
+/// * `k` - The byte length of the modulus.
+/// * `label` - The label bound to the ciphertext, usually empty. The same label is needed to unpad.
+/// * `hash` - The hash function used for the label hash and MGF1.
+/// * `rng` - The random number generator used to draw the seed of `hLen` bytes.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the `k` byte encoding, or `Error::MessageTooLong` if the message
+///   is longer than `k - 2 * hLen - 2` bytes.
+pub fn oaep_pad<R: RngCore + CryptoRng + ?Sized>(
+    message: &[u8],
+    k: usize,
+    label: &[u8],
+    hash: HashAlg,
+    rng: &mut R,
+) -> Result<Vec<u8>, Error> {
+    let h_len = hash.digest_size();
+    if message.len() + oaep_overhead(hash) > k {
+        return Err(Error::MessageTooLong);
+    }
+
+    let mut db = Vec::with_capacity(k - h_len - 1);
+    db.extend_from_slice(&hash.digest(label));
+    db.resize(k - message.len() - h_len - 2, 0x00);
+    db.push(0x01);
+    db.extend_from_slice(message);
+
+    let mut seed = vec![0u8; h_len];
+    rng.fill_bytes(&mut seed);
+    let db_mask = mgf1(hash, &seed, db.len());
+    xor_in_place(&mut db, &db_mask);
+    xor_in_place(&mut seed, &mgf1(hash, &db, h_len));
+
+    let mut padded_message = Vec::with_capacity(k);
+    padded_message.push(0x00);
+    padded_message.extend_from_slice(&seed);
+    padded_message.extend_from_slice(&db);
+    Ok(padded_message)
+}
+
+/// The `oaep_unpad` function removes RSAES-OAEP padding made with the given hash function and MGF1
+/// (RFC 8017 §7.1.2).
+/// Like `pkcs1_unpad`, every check is made before the single decision, so the running time does not
+/// reveal which one failed.
+///
+/// # Arguments
+///
+/// * `padded_message` - The decrypted block as exactly `k` bytes, leading zeros included.
+/// * `label` - The label the message was padded with.
+/// * `hash` - The hash function the message was padded with.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the message, or `Error::InvalidPadding` if the first byte is not
+///   zero, the label hash does not match or the `0x01` separator is missing.
+pub fn oaep_unpad(padded_message: &[u8], label: &[u8], hash: HashAlg) -> Result<Vec<u8>, Error> {
+    let h_len = hash.digest_size();
+    if padded_message.len() < oaep_overhead(hash) {
+        return Err(Error::InvalidPadding);
+    }
+
+    let (masked_seed, masked_db) = padded_message[1..].split_at(h_len);
+    let mut seed = masked_seed.to_vec();
+    xor_in_place(&mut seed, &mgf1(hash, masked_db, h_len));
+    let mut db = masked_db.to_vec();
+    xor_in_place(&mut db, &mgf1(hash, &seed, masked_db.len()));
+
+    let label_hash = hash.digest(label);
+    let difference = db.iter().zip(&label_hash).fold(0u8, |difference, (a, b)| difference | (a ^ b));
+    let mut valid = ct_eq(padded_message[0], 0x00) & ct_eq(difference, 0x00);
+
+    // The padding is zero bytes up to the first nonzero byte, which must be the separator
+    let mut looking = 0xffu8;
+    let mut separator = 0usize;
+    for (i, &byte) in db.iter().enumerate().skip(h_len) {
+        let found = looking & !ct_eq(byte, 0x00);
+        valid &= !found | ct_eq(byte, 0x01);
+        separator |= i & ct_widen(found);
+        looking &= !found;
+    }
+    valid &= !looking;
+
+    if valid == 0 {
+        return Err(Error::InvalidPadding);
+    }
+    Ok(db.split_off(separator + 1))
+}
+
+/// The padding scheme a message was encrypted with, recorded in the ciphertext container so the
+/// recipient knows how to undo it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PaddingScheme {
+    /// Length-framed chunks encrypted without randomized padding. Equal messages give equal
+    /// ciphertexts, so this is only meant for teaching.
+    Textbook,
+    /// Every chunk is padded with RSAES-PKCS1-v1_5 before it is encrypted.
+    #[default]
+    Pkcs1v15,
+    /// Every chunk is padded with RSAES-OAEP using the hash for the label hash and MGF1, and bound to
+    /// the label. The container records the hash and whether the label is empty, but not the label
+    /// itself, so the recipient has to pass the same label to decrypt.
+    Oaep { hash: HashAlg, label: Vec<u8> },
+}
+
+impl PaddingScheme {
+    /// The `oaep` function returns the OAEP scheme with the given hash and the empty label.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash function used for the label hash and MGF1.
+    ///
+    /// # Returns
+    ///
+    /// * `PaddingScheme` - Returns `PaddingScheme::Oaep` without a label.
+    pub fn oaep(hash: HashAlg) -> Self {
+        PaddingScheme::Oaep { hash, label: Vec::new() }
+    }
+
+    /// The `with_label` function binds a label to an OAEP scheme. The other schemes have no label,
+    /// so they are returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label bound to every chunk.
+    ///
+    /// # Returns
+    ///
+    /// * `PaddingScheme` - Returns the scheme with the label.
+    pub fn with_label(self, label: &[u8]) -> Self {
+        match self {
+            PaddingScheme::Oaep { hash, .. } => PaddingScheme::Oaep { hash, label: label.to_vec() },
+            scheme => scheme,
+        }
+    }
+
+    /// Returns the label the chunks are bound to, which is empty for every scheme but a labelled OAEP.
+    pub fn label(&self) -> &[u8] {
+        match self {
+            PaddingScheme::Oaep { label, .. } => label,
+            _ => &[],
+        }
+    }
+
+    /// The `id` function returns the byte that identifies the scheme in the ciphertext container. For
+    /// OAEP it records the hash and whether the label is empty: 2, 3 and 4 are SHA-256, SHA-1 and
+    /// SHA-512 without a label, and 5, 6 and 7 the same hashes with one.
+    ///
+    /// # Returns
+    ///
+    /// * `u8` - Returns the scheme id.
+    pub fn id(&self) -> u8 {
+        match self {
+            PaddingScheme::Textbook => 0,
+            PaddingScheme::Pkcs1v15 => 1,
+            PaddingScheme::Oaep { hash, label } => {
+                let hash_id = match hash {
+                    HashAlg::Sha256 => 0,
+                    HashAlg::Sha1 => 1,
+                    HashAlg::Sha512 => 2,
+                };
+                if label.is_empty() { 2 + hash_id } else { 5 + hash_id }
+            }
+        }
+    }
+
+    /// The `from_id` function looks up a scheme by its ciphertext container id. The label is not
+    /// recorded, so an OAEP scheme comes back without one; use `is_labelled_id` to tell whether the
+    /// message was bound to a label.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The scheme id.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PaddingScheme>` - Returns the scheme, or `None` if the id is unknown.
+    pub fn from_id(id: u8) -> Option<Self> {
+        let hash = |hash_id| match hash_id {
+            0 => HashAlg::Sha256,
+            1 => HashAlg::Sha1,
+            _ => HashAlg::Sha512,
+        };
+        match id {
+            0 => Some(PaddingScheme::Textbook),
+            1 => Some(PaddingScheme::Pkcs1v15),
+            2..=4 => Some(PaddingScheme::oaep(hash(id - 2))),
+            5..=7 => Some(PaddingScheme::oaep(hash(id - 5))),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the scheme id records an OAEP scheme with a nonempty label.
+    pub fn is_labelled_id(id: u8) -> bool {
+        (5..=7).contains(&id)
+    }
+
+    /// The `chunk_size` function calculates how many message bytes fit in one block under this scheme.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The modulus of the recipient's key.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - Returns the chunk size in bytes, which is zero if the modulus is too small.
+    pub fn chunk_size(&self, n: &BigUint) -> usize {
+        match self {
+            PaddingScheme::Textbook => calculate_chunk_size(n),
+            PaddingScheme::Pkcs1v15 => (n.bits().div_ceil(8) as usize).saturating_sub(PKCS1_OVERHEAD),
+            PaddingScheme::Oaep { hash, .. } => (n.bits().div_ceil(8) as usize).saturating_sub(oaep_overhead(*hash)),
+        }
+    }
+
+    /// Encodes a chunk as the bytes of the integer to encrypt, `k` bytes wide for the padded schemes.
+    pub(crate) fn encode_chunk<R: RngCore + CryptoRng + ?Sized>(
+        &self,
+        chunk: &[u8],
+        k: usize,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, Error> {
+        match self {
+            PaddingScheme::Textbook => Ok(chunk.to_vec()),
+            PaddingScheme::Pkcs1v15 => pkcs1_pad(chunk, k, rng),
+            PaddingScheme::Oaep { hash, label } => oaep_pad(chunk, k, label, *hash, rng),
+        }
+    }
+
+    /// Decodes a decrypted block, given as `k` bytes, back into a whole chunk.
+    pub(crate) fn decode_chunk(&self, block: &[u8], chunk_size: usize) -> Result<Vec<u8>, Error> {
+        match self {
+            PaddingScheme::Textbook => {
+                let (high, chunk) = block.split_at(block.len() - chunk_size);
+                if high.iter().any(|&b| b != 0) {
+                    return Err(Error::InvalidFraming);
+                }
+                Ok(chunk.to_vec())
+            }
+            PaddingScheme::Pkcs1v15 => match pkcs1_unpad(block)? {
+                chunk if chunk.len() == chunk_size => Ok(chunk.to_vec()),
+                _ => Err(Error::InvalidPadding),
+            },
+            PaddingScheme::Oaep { hash, label } => match oaep_unpad(block, label, *hash)? {
+                chunk if chunk.len() == chunk_size => Ok(chunk),
+                _ => Err(Error::InvalidPadding),
+            },
+        }
+    }
 }
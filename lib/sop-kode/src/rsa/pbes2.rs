@@ -0,0 +1,204 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rand::{CryptoRng, RngCore};
+
+use crate::aes::{self, BLOCK_SIZE};
+use crate::hash::pbkdf2_hmac_sha256;
+use crate::Error;
+use crate::der::{encode_integer, encode_null, encode_octet_string, encode_oid, encode_sequence, DerReader, TAG_INTEGER, TAG_SEQUENCE};
+use super::keys::RSA;
+use super::pem;
+
+/// The PEM label of an EncryptedPrivateKeyInfo.
+pub const ENCRYPTED_PRIVATE_KEY_LABEL: &str = "ENCRYPTED PRIVATE KEY";
+
+/// The number of PBKDF2 iterations used when encrypting a key.
+pub const PBKDF2_ITERATIONS: u32 = 100_000;
+/// The largest PBKDF2 iteration count accepted when decrypting a key, so a crafted file cannot make
+/// the key derivation run for hours.
+pub const MAX_PBKDF2_ITERATIONS: u32 = 10 * PBKDF2_ITERATIONS;
+/// The length of the PBKDF2 salt in bytes.
+const SALT_LENGTH: usize = 16;
+
+/// The encoded object identifier of PBES2, 1.2.840.113549.1.5.13.
+const OID_PBES2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0d];
+/// The encoded object identifier of PBKDF2, 1.2.840.113549.1.5.12.
+const OID_PBKDF2: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x05, 0x0c];
+/// The encoded object identifier of hmacWithSHA256, 1.2.840.113549.2.9.
+const OID_HMAC_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x09];
+/// The encoded object identifiers of aes128-CBC, aes192-CBC and aes256-CBC, 2.16.840.1.101.3.4.1.{2,22,42}.
+const OID_AES_CBC: [(&[u8], usize); 3] = [
+    (&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x02], 16),
+    (&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x16], 24),
+    (&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a], 32),
+];
+
+/// The parameters needed to derive the key and decrypt an EncryptedPrivateKeyInfo.
+struct Pbes2Params<'a> {
+    salt: &'a [u8],
+    iterations: u32,
+    key_length: usize,
+    iv: [u8; BLOCK_SIZE],
+}
+
+/// Reads the PBES2-params (RFC 8018 §A.4) of an EncryptedPrivateKeyInfo. Only PBKDF2 with
+/// HMAC-SHA256 and AES-CBC are supported.
+fn read_pbes2_params<'a>(reader: &mut DerReader<'a>) -> Result<Pbes2Params<'a>, Error> {
+    let mut algorithm = reader.read_sequence()?;
+    if algorithm.read_oid()? != OID_PBES2 {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+    let mut params = algorithm.read_sequence()?;
+    algorithm.finish()?;
+
+    // keyDerivationFunc: PBKDF2-params ::= SEQUENCE { salt, iterationCount, keyLength OPTIONAL, prf }
+    let mut kdf = params.read_sequence()?;
+    if kdf.read_oid()? != OID_PBKDF2 {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+    let mut kdf_params = kdf.read_sequence()?;
+    kdf.finish()?;
+    let salt = kdf_params.read_octet_string()?;
+    let iterations = read_small_integer(&mut kdf_params)?;
+    if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(Error::InvalidDer("iteration count out of range"));
+    }
+    let stated_key_length = match kdf_params.peek_tag() {
+        Some(TAG_INTEGER) => Some(read_small_integer(&mut kdf_params)? as usize),
+        _ => None,
+    };
+    // The default PRF is hmacWithSHA1, which is not supported
+    let mut prf = kdf_params.read_sequence().map_err(|_| Error::UnsupportedAlgorithm)?;
+    if prf.read_oid()? != OID_HMAC_WITH_SHA256 {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+    if !prf.is_empty() {
+        prf.read_null()?;
+    }
+    prf.finish()?;
+    kdf_params.finish()?;
+
+    // encryptionScheme: AES-CBC with the IV as parameter
+    let mut scheme = params.read_sequence()?;
+    let scheme_oid = scheme.read_oid()?;
+    let key_length = OID_AES_CBC
+        .iter()
+        .find(|(oid, _)| *oid == scheme_oid)
+        .map(|&(_, key_length)| key_length)
+        .ok_or(Error::UnsupportedAlgorithm)?;
+    let iv = scheme
+        .read_octet_string()?
+        .try_into()
+        .map_err(|_| Error::InvalidDer("IV must be one block"))?;
+    scheme.finish()?;
+    params.finish()?;
+
+    if stated_key_length.is_some_and(|length| length != key_length) {
+        return Err(Error::InvalidDer("key length does not match cipher"));
+    }
+
+    Ok(Pbes2Params { salt, iterations, key_length, iv })
+}
+
+/// Reads an INTEGER that must fit in a u32.
+fn read_small_integer(reader: &mut DerReader) -> Result<u32, Error> {
+    reader
+        .read_integer()?
+        .to_u32()
+        .ok_or(Error::InvalidDer("integer out of range"))
+}
+
+impl RSA {
+    /// The `to_encrypted_pem` function encrypts the key pair with a password and encodes it as a PEM
+    /// `ENCRYPTED PRIVATE KEY` block. The key is stored as PKCS#8 encrypted with PBES2, using
+    /// PBKDF2-HMAC-SHA256 and AES-256-CBC, which OpenSSL can read.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password protecting the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the PEM text, or `Error::MissingPrimeFactors` if the private key
+    ///   does not hold `p` and `q`.
+    pub fn to_encrypted_pem(&self, password: &str) -> Result<String, Error> {
+        self.to_encrypted_pem_with_rng(password, &mut rand::thread_rng())
+    }
+
+    /// The `to_encrypted_pem_with_rng` function works like `to_encrypted_pem`, but draws the salt and
+    /// IV from the given random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password protecting the key.
+    /// * `rng` - The random number generator used to draw the salt and IV.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the PEM text, or `Error::MissingPrimeFactors` if the private key
+    ///   does not hold `p` and `q`.
+    pub fn to_encrypted_pem_with_rng<R: RngCore + CryptoRng + ?Sized>(&self, password: &str, rng: &mut R) -> Result<String, Error> {
+        let (aes256_cbc, key_length) = OID_AES_CBC[2];
+
+        let mut salt = [0u8; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let mut iv = [0u8; BLOCK_SIZE];
+        rng.fill_bytes(&mut iv);
+
+        let key = pbkdf2_hmac_sha256(password.as_bytes(), &salt, PBKDF2_ITERATIONS, key_length);
+        let encrypted_data = aes::cbc_encrypt(&key, &iv, &self.to_pkcs8_der()?)?;
+
+        let kdf = encode_sequence(&[
+            encode_oid(OID_PBKDF2),
+            encode_sequence(&[
+                encode_octet_string(&salt),
+                encode_integer(&BigUint::from(PBKDF2_ITERATIONS)),
+                encode_sequence(&[encode_oid(OID_HMAC_WITH_SHA256), encode_null()]),
+            ]),
+        ]);
+        let scheme = encode_sequence(&[encode_oid(aes256_cbc), encode_octet_string(&iv)]);
+        let der = encode_sequence(&[
+            encode_sequence(&[encode_oid(OID_PBES2), encode_sequence(&[kdf, scheme])]),
+            encode_octet_string(&encrypted_data),
+        ]);
+
+        Ok(pem::encode(ENCRYPTED_PRIVATE_KEY_LABEL, &der))
+    }
+
+    /// The `from_encrypted_pem` function decrypts a PEM `ENCRYPTED PRIVATE KEY` block with a password.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM text.
+    /// * `password` - The password protecting the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, `Error::WrongPassword` if the decrypted key has the wrong
+    ///   padding or does not start with a DER SEQUENCE, the decoding error if it does but is not a valid PKCS#8
+    ///   key, which means the file is corrupt, or another error if the encrypted structure around it is corrupt
+    ///   or uses an unsupported algorithm or more than `MAX_PBKDF2_ITERATIONS` iterations.
+    pub fn from_encrypted_pem(pem: &str, password: &str) -> Result<Self, Error> {
+        let der = pem::decode_with_label(pem, ENCRYPTED_PRIVATE_KEY_LABEL)?;
+
+        let mut reader = DerReader::new(&der);
+        let mut info = reader.read_sequence()?;
+        reader.finish()?;
+        let params = read_pbes2_params(&mut info)?;
+        let encrypted_data = info.read_octet_string()?;
+        info.finish()?;
+        if encrypted_data.is_empty() || encrypted_data.len() % BLOCK_SIZE != 0 {
+            return Err(Error::InvalidDer("encrypted key is not whole blocks"));
+        }
+
+        let key = pbkdf2_hmac_sha256(password.as_bytes(), params.salt, params.iterations, params.key_length);
+        let private_key_info = aes::cbc_decrypt(&key, &params.iv, encrypted_data).map_err(|_| Error::WrongPassword)?;
+
+        // A wrong key gives valid padding about once in 256 tries, but then almost never a SEQUENCE as well,
+        // so a key that starts like PKCS#8 and does not decode was damaged after it was encrypted
+        if private_key_info.first() != Some(&TAG_SEQUENCE) {
+            return Err(Error::WrongPassword);
+        }
+        Self::from_pkcs8_der(&private_key_info)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::Error;
+use crate::base64;
+
+/// The number of Base64 characters per line in PEM output.
+const LINE_LENGTH: usize = 64;
+
+/// The `encode` function wraps DER bytes in PEM armor with the given label (RFC 7468).
+///
+/// # Arguments
+///
+/// * `label` - The label of the armor, for example `RSA PUBLIC KEY`.
+/// * `der` - The DER bytes to armor.
+///
+/// # Returns
+///
+/// * `String` - Returns the PEM text.
+pub fn encode(label: &str, der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// The `decode` function removes the PEM armor from the first block in the text.
+///
+/// # Arguments
+///
+/// * `pem` - The PEM text.
+///
+/// # Returns
+///
+/// * `Result<(String, Vec<u8>), Error>` - Returns the label and the DER bytes, or `Error::InvalidPem`
+///   if the armor is malformed.
+pub fn decode(pem: &str) -> Result<(String, Vec<u8>), Error> {
+    let mut lines = pem.lines().map(str::trim);
+
+    let label = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix("-----BEGIN ")?.strip_suffix("-----"))
+        .ok_or(Error::InvalidPem)?
+        .to_string();
+    let end = format!("-----END {}-----", label);
+
+    let mut body = String::new();
+    for line in lines {
+        if line == end {
+            let der = base64::decode(&body).map_err(|_| Error::InvalidPem)?;
+            return Ok((label, der));
+        }
+        if line.contains(':') {
+            // Encapsulated headers such as `Proc-Type` are not supported
+            return Err(Error::InvalidPem);
+        }
+        body.push_str(line);
+    }
+
+    Err(Error::InvalidPem)
+}
+
+/// The `decode_with_label` function removes the PEM armor and checks that it carries the expected label.
+///
+/// # Arguments
+///
+/// * `pem` - The PEM text.
+/// * `label` - The expected label.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the DER bytes, or `Error::InvalidPem` if the armor is malformed
+///   or has another label.
+pub fn decode_with_label(pem: &str, label: &str) -> Result<Vec<u8>, Error> {
+    let (actual_label, der) = decode(pem)?;
+    if actual_label != label {
+        return Err(Error::InvalidPem);
+    }
+    Ok(der)
+}
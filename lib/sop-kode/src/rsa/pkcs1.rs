@@ -0,0 +1,159 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::Error;
+use crate::der::{encode_integer, encode_sequence, DerReader};
+use super::encryption::{PrivateKey, PublicKey};
+use super::keys::RSA;
+use super::pem;
+
+/// The PEM label of a PKCS#1 `RSAPublicKey`.
+pub const PUBLIC_KEY_LABEL: &str = "RSA PUBLIC KEY";
+/// The PEM label of a PKCS#1 `RSAPrivateKey`.
+pub const PRIVATE_KEY_LABEL: &str = "RSA PRIVATE KEY";
+
+impl PublicKey {
+    /// The `to_pkcs1_der` function encodes the key as a PKCS#1 `RSAPublicKey` (RFC 8017 §A.1.1).
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - Returns the DER encoding of the key.
+    pub fn to_pkcs1_der(&self) -> Vec<u8> {
+        encode_sequence(&[encode_integer(&self.n), encode_integer(&self.e)])
+    }
+
+    /// The `from_pkcs1_der` function decodes a PKCS#1 `RSAPublicKey`.
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The DER encoding of the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or `Error::InvalidDer` if the encoding is malformed.
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self, Error> {
+        let mut reader = DerReader::new(der);
+        let mut sequence = reader.read_sequence()?;
+        reader.finish()?;
+
+        let n = sequence.read_integer()?;
+        let e = sequence.read_integer()?;
+        sequence.finish()?;
+
+        Ok(PublicKey { n, e })
+    }
+
+    /// The `to_pkcs1_pem` function encodes the key as a PEM `RSA PUBLIC KEY` block.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the PEM text.
+    pub fn to_pkcs1_pem(&self) -> String {
+        pem::encode(PUBLIC_KEY_LABEL, &self.to_pkcs1_der())
+    }
+
+    /// The `from_pkcs1_pem` function decodes a PEM `RSA PUBLIC KEY` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or an error if the armor or encoding is malformed.
+    pub fn from_pkcs1_pem(pem: &str) -> Result<Self, Error> {
+        Self::from_pkcs1_der(&pem::decode_with_label(pem, PUBLIC_KEY_LABEL)?)
+    }
+}
+
+impl RSA {
+    /// The `to_pkcs1_der` function encodes the key pair as a PKCS#1 `RSAPrivateKey` (RFC 8017 §A.1.2).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the DER encoding of the key pair, or `Error::MissingPrimeFactors`
+    ///   if the private key does not hold `p` and `q`.
+    pub fn to_pkcs1_der(&self) -> Result<Vec<u8>, Error> {
+        let private_key = self.private_key();
+        let (p, q) = private_key.primes().ok_or(Error::MissingPrimeFactors)?;
+        let (dp, dq, qinv) = private_key.crt_values().ok_or(Error::MissingPrimeFactors)?;
+
+        Ok(encode_sequence(&[
+            encode_integer(&BigUint::zero()), // version: two-prime
+            encode_integer(&self.public_key.n),
+            encode_integer(&self.public_key.e),
+            encode_integer(private_key.d()),
+            encode_integer(p),
+            encode_integer(q),
+            encode_integer(dp),
+            encode_integer(dq),
+            encode_integer(qinv),
+        ]))
+    }
+
+    /// The `from_pkcs1_der` function decodes a PKCS#1 `RSAPrivateKey`. The key is not validated,
+    /// call `validate` to check it.
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The DER encoding of the key pair.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, or an error if the encoding is malformed or the
+    ///   key is not a two-prime key.
+    pub fn from_pkcs1_der(der: &[u8]) -> Result<Self, Error> {
+        let mut reader = DerReader::new(der);
+        let mut sequence = reader.read_sequence()?;
+        reader.finish()?;
+
+        if !sequence.read_integer()?.is_zero() {
+            return Err(Error::InvalidDer("unsupported RSAPrivateKey version"));
+        }
+        let n = sequence.read_integer()?;
+        let e = sequence.read_integer()?;
+        let d = sequence.read_integer()?;
+        let p = sequence.read_integer()?;
+        let q = sequence.read_integer()?;
+        // dP, dQ and qInv are recomputed from p, q and d
+        for _ in 0..3 {
+            sequence.read_integer()?;
+        }
+        sequence.finish()?;
+
+        if p <= BigUint::one() || q <= BigUint::one() || p == q {
+            return Err(Error::InvalidDer("prime factors are smaller than 2 or equal"));
+        }
+        let private_key = PrivateKey::from_primes(p, q, d)
+            .map_err(|_| Error::InvalidDer("prime factors are not coprime"))?
+            .with_blinding(e.clone())?;
+        if private_key.n() != &n {
+            return Err(Error::InvalidDer("modulus is not the product of the primes"));
+        }
+
+        Ok(RSA::from_keys(PublicKey { n, e }, private_key))
+    }
+
+    /// The `to_pkcs1_pem` function encodes the key pair as a PEM `RSA PRIVATE KEY` block.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the PEM text, or `Error::MissingPrimeFactors` if the private key
+    ///   does not hold `p` and `q`.
+    pub fn to_pkcs1_pem(&self) -> Result<String, Error> {
+        Ok(pem::encode(PRIVATE_KEY_LABEL, &self.to_pkcs1_der()?))
+    }
+
+    /// The `from_pkcs1_pem` function decodes a PEM `RSA PRIVATE KEY` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, or an error if the armor or encoding is malformed.
+    pub fn from_pkcs1_pem(pem: &str) -> Result<Self, Error> {
+        Self::from_pkcs1_der(&pem::decode_with_label(pem, PRIVATE_KEY_LABEL)?)
+    }
+}
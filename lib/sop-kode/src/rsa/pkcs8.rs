@@ -0,0 +1,159 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::Error;
+use crate::der::{
+    encode_bit_string, encode_integer, encode_null, encode_octet_string, encode_oid, encode_sequence,
+    DerReader, TAG_NULL,
+};
+use super::encryption::PublicKey;
+use super::keys::RSA;
+use super::pem;
+
+/// The PEM label of a SubjectPublicKeyInfo.
+pub const PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+/// The PEM label of an unencrypted PKCS#8 PrivateKeyInfo.
+pub const PRIVATE_KEY_LABEL: &str = "PRIVATE KEY";
+
+/// The encoded object identifier of rsaEncryption, 1.2.840.113549.1.1.1.
+pub const OID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+/// Encodes the rsaEncryption AlgorithmIdentifier, whose parameters are NULL.
+fn encode_algorithm_identifier() -> Vec<u8> {
+    encode_sequence(&[encode_oid(OID_RSA_ENCRYPTION), encode_null()])
+}
+
+/// Reads an AlgorithmIdentifier and checks that it is rsaEncryption.
+fn read_algorithm_identifier(reader: &mut DerReader) -> Result<(), Error> {
+    let mut algorithm = reader.read_sequence()?;
+    if algorithm.read_oid()? != OID_RSA_ENCRYPTION {
+        return Err(Error::InvalidDer("algorithm is not rsaEncryption"));
+    }
+    // The parameters must be NULL, but some encoders leave them out
+    if algorithm.peek_tag() == Some(TAG_NULL) {
+        algorithm.read_null()?;
+    }
+    algorithm.finish()
+}
+
+impl PublicKey {
+    /// The `to_spki_der` function encodes the key as a SubjectPublicKeyInfo (RFC 5280 §4.1)
+    /// wrapping the PKCS#1 `RSAPublicKey`.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - Returns the DER encoding of the key.
+    pub fn to_spki_der(&self) -> Vec<u8> {
+        encode_sequence(&[encode_algorithm_identifier(), encode_bit_string(&self.to_pkcs1_der())])
+    }
+
+    /// The `from_spki_der` function decodes an rsaEncryption SubjectPublicKeyInfo.
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The DER encoding of the key.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or `Error::InvalidDer` if the encoding is malformed
+    ///   or the key is not an RSA key.
+    pub fn from_spki_der(der: &[u8]) -> Result<Self, Error> {
+        let mut reader = DerReader::new(der);
+        let mut spki = reader.read_sequence()?;
+        reader.finish()?;
+
+        read_algorithm_identifier(&mut spki)?;
+        let public_key = spki.read_bit_string()?;
+        spki.finish()?;
+
+        Self::from_pkcs1_der(public_key)
+    }
+
+    /// The `to_spki_pem` function encodes the key as a PEM `PUBLIC KEY` block.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - Returns the PEM text.
+    pub fn to_spki_pem(&self) -> String {
+        pem::encode(PUBLIC_KEY_LABEL, &self.to_spki_der())
+    }
+
+    /// The `from_spki_pem` function decodes a PEM `PUBLIC KEY` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PublicKey, Error>` - Returns the key, or an error if the armor or encoding is malformed.
+    pub fn from_spki_pem(pem: &str) -> Result<Self, Error> {
+        Self::from_spki_der(&pem::decode_with_label(pem, PUBLIC_KEY_LABEL)?)
+    }
+}
+
+impl RSA {
+    /// The `to_pkcs8_der` function encodes the key pair as a PKCS#8 PrivateKeyInfo (RFC 5208 §5)
+    /// wrapping the PKCS#1 `RSAPrivateKey`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the DER encoding of the key pair, or `Error::MissingPrimeFactors`
+    ///   if the private key does not hold `p` and `q`.
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>, Error> {
+        Ok(encode_sequence(&[
+            encode_integer(&BigUint::zero()), // version: v1
+            encode_algorithm_identifier(),
+            encode_octet_string(&self.to_pkcs1_der()?),
+        ]))
+    }
+
+    /// The `from_pkcs8_der` function decodes an rsaEncryption PKCS#8 PrivateKeyInfo. Optional
+    /// attributes and the public key of a v2 OneAsymmetricKey (RFC 5958) are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The DER encoding of the key pair.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, or an error if the encoding is malformed or the
+    ///   key is not an RSA key.
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, Error> {
+        let mut reader = DerReader::new(der);
+        let mut info = reader.read_sequence()?;
+        reader.finish()?;
+
+        let version = info.read_integer()?;
+        if !version.is_zero() && !version.is_one() {
+            return Err(Error::InvalidDer("unsupported PrivateKeyInfo version"));
+        }
+        read_algorithm_identifier(&mut info)?;
+        let private_key = info.read_octet_string()?;
+
+        Self::from_pkcs1_der(private_key)
+    }
+
+    /// The `to_pkcs8_pem` function encodes the key pair as a PEM `PRIVATE KEY` block.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - Returns the PEM text, or `Error::MissingPrimeFactors` if the private key
+    ///   does not hold `p` and `q`.
+    pub fn to_pkcs8_pem(&self) -> Result<String, Error> {
+        Ok(pem::encode(PRIVATE_KEY_LABEL, &self.to_pkcs8_der()?))
+    }
+
+    /// The `from_pkcs8_pem` function decodes a PEM `PRIVATE KEY` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RSA, Error>` - Returns the key pair, or an error if the armor or encoding is malformed.
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self, Error> {
+        Self::from_pkcs8_der(&pem::decode_with_label(pem, PRIVATE_KEY_LABEL)?)
+    }
+}
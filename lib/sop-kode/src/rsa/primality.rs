@@ -1,45 +1,72 @@
 use num_bigint::{BigUint, RandBigInt};
-use num_traits::{FromPrimitive, One, Zero};
+use rand::{CryptoRng, RngCore};
+use num_traits::{One, Zero};
+
+use crate::Error;
+
+/// The small primes trial-divided out of every candidate before the Miller-Rabin rounds.
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
 
 /// The `miller_rabin` function is an implementation of the Miller-Rabin primality test.
 /// The Miller-Rabin test is a probabilistic primality test: an algorithm which determines
 /// whether a given number is likely to be prime, similar to the Fermat primality test
 /// and the Solovay–Strassen primality test.
 ///
+/// The bases are drawn from `thread_rng`. Use `miller_rabin_with_rng` to choose the generator.
+///
 /// # Arguments
 ///
 /// * `n` - A BigUint value representing the number to be tested for primality.
 /// * `k` - The number of rounds of testing to perform. The higher the value of `k`,
-///          the more accurate the test is. A common value for `k` is 5.
+///   the more accurate the test is. A common value for `k` is 5.
 ///
 /// # Returns
 ///
 /// * `bool` - Returns `true` if `n` is likely to be prime, and `false` otherwise.
 pub fn miller_rabin(n: &BigUint, k: usize) -> bool {
-    let small_primes = &[
-        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
-        97,
-    ];
+    miller_rabin_with_rng(n, k, &mut rand::thread_rng())
+}
 
-    for &prime in small_primes {
-        if n == &BigUint::from(prime as usize) {
+/// The `miller_rabin_with_rng` function runs the Miller-Rabin test with bases drawn uniformly
+/// from `[2, n - 2]`. Because the bases are random, no composite can be crafted to pass every
+/// round: each round lets a composite through with a probability of at most 1/4.
+///
+/// # Arguments
+///
+/// * `n` - A BigUint value representing the number to be tested for primality.
+/// * `k` - The number of rounds of testing to perform.
+/// * `rng` - The random number generator used to draw the bases.
+///
+/// # Returns
+///
+/// * `bool` - Returns `true` if `n` is likely to be prime, and `false` otherwise.
+pub fn miller_rabin_with_rng<R: RngCore + CryptoRng + ?Sized>(n: &BigUint, k: usize, rng: &mut R) -> bool {
+    if n < &BigUint::from(2u8) {
+        return false;
+    }
+    for &prime in &SMALL_PRIMES {
+        if n == &BigUint::from(prime) {
             return true;
-        } else if n % prime as usize == BigUint::zero() {
+        } else if (n % prime).is_zero() {
             return false;
         }
     }
 
     let n_minus_one = n - BigUint::one();
-    let d = n_minus_one.clone() >> n_minus_one.trailing_zeros().unwrap() as usize;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+    let two = BigUint::from(2u8);
 
-    'outer: for i in 0..k {
-        let a = BigUint::from_u32(small_primes[i % small_primes.len()] as u32).unwrap();
+    'outer: for _ in 0..k {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
         let mut x = a.modpow(&d, n);
         if x.is_one() || x == n_minus_one {
             continue;
         }
-        for _ in 0..n_minus_one.trailing_zeros().unwrap() {
-            x = x.modpow(&BigUint::from(2u64), n);
+        for _ in 0..s {
+            x = x.modpow(&two, n);
             if x.is_one() {
                 return false;
             }
@@ -56,24 +83,58 @@ pub fn miller_rabin(n: &BigUint, k: usize) -> bool {
 ///
 /// # Arguments
 ///
-/// * `bits` - The number of bits in the prime number to be generated. The function will generate a prime number that is approximately this size.
+/// * `bits` - The number of bits in the prime number to be generated. The top two bits of the prime are always set,
+///   so the product of two such primes has exactly `2 * bits` bits.
+/// * `rng` - The random number generator used to draw the starting candidate and the Miller-Rabin bases.
 ///
 /// # Returns
 ///
-/// * `BigUint` - Returns a prime number of approximately `bits` bits.
-pub fn generate_prime(bits: usize) -> BigUint {
-    let mut rng = rand::thread_rng();
-    let mut n = rng.gen_biguint(bits as u64);
-    let zero = BigUint::zero();
-    // Ensure n is odd
-    if &n % 2usize == zero {
-        n += 1usize;
-    }
+/// * `Result<BigUint, Error>` - Returns a prime number of exactly `bits` bits, or `Error::BitsTooSmall`
+///   if `bits` is smaller than 2.
+pub fn generate_prime<R: RngCore + CryptoRng + ?Sized>(bits: usize, rng: &mut R) -> Result<BigUint, Error> {
+    generate_prime_with(bits, 5, rng)
+}
 
-    while !miller_rabin(&n, 5) {
-        // Increment by 2 to ensure n stays odd
-        n += 2usize;
+/// The `generate_prime_with` function generates a prime number of a specified bit size using
+/// the given number of Miller-Rabin rounds and random number generator.
+///
+/// Like FIPS 186 key generation, the top two bits of every candidate are set, so the product of
+/// two primes of `bits` bits always has exactly `2 * bits` bits.
+///
+/// # Arguments
+///
+/// * `bits` - The number of bits in the prime number to be generated.
+/// * `rounds` - The number of Miller-Rabin rounds used to test each candidate.
+/// * `rng` - The random number generator used to draw the starting candidate and the Miller-Rabin bases.
+///
+/// # Returns
+///
+/// * `Result<BigUint, Error>` - Returns a prime number of exactly `bits` bits, or `Error::BitsTooSmall`
+///   if `bits` is smaller than 2 and leaves no room for the top two bits.
+pub fn generate_prime_with<R: RngCore + CryptoRng + ?Sized>(
+    bits: usize,
+    rounds: usize,
+    rng: &mut R,
+) -> Result<BigUint, Error> {
+    if bits < 2 {
+        return Err(Error::BitsTooSmall { bits, min: 2 });
     }
 
-    n
+    loop {
+        let mut n = rng.gen_biguint(bits as u64);
+
+        // Set the top two bits and ensure n is odd
+        n.set_bit(bits as u64 - 1, true);
+        n.set_bit(bits as u64 - 2, true);
+        n.set_bit(0, true);
+
+        while n.bits() == bits as u64 {
+            if miller_rabin_with_rng(&n, rounds, rng) {
+                return Ok(n);
+            }
+            // Increment by 2 to ensure n stays odd
+            n += 2usize;
+        }
+        // The search ran past `bits` bits, so start over from a fresh candidate
+    }
 }
@@ -0,0 +1,281 @@
+use num_bigint::BigUint;
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use crate::hash::HashAlg;
+use crate::Error;
+use super::encryption::PublicKey;
+use super::keys::RSA;
+use super::padding::{mgf1, xor_in_place};
+use super::utils::to_fixed_bytes;
+
+/// The last byte of every EMSA-PSS encoding.
+const PSS_TRAILER: u8 = 0xbc;
+
+/// The DER encoding of the DigestInfo `AlgorithmIdentifier` and digest header for each hash
+/// function (RFC 8017 §9.2, note 1). The digest follows directly.
+fn digest_info_prefix(hash: HashAlg) -> &'static [u8] {
+    match hash {
+        HashAlg::Sha1 => &[
+            0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+        ],
+        HashAlg::Sha256 => &[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+            0x00, 0x04, 0x20,
+        ],
+        HashAlg::Sha512 => &[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+            0x00, 0x04, 0x40,
+        ],
+    }
+}
+
+/// The `emsa_pkcs1_v15_encode` function encodes a message for an RSASSA-PKCS1-v1_5 signature
+/// (RFC 8017 §9.2): `0x00 || 0x01 || 0xff... || 0x00 || DigestInfo`, where the `0xff` padding is at
+/// least eight bytes and DigestInfo names the hash function next to the digest.
+///
+/// # Arguments
+///
+/// * `message` - The message to be signed.
+/// * `hash` - The hash function to digest the message with.
+/// * `k` - The byte length of the modulus.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the `k` byte encoding, or `Error::KeyTooSmall` if the modulus
+///   cannot hold the DigestInfo and the padding.
+pub fn emsa_pkcs1_v15_encode(message: &[u8], hash: HashAlg, k: usize) -> Result<Vec<u8>, Error> {
+    let prefix = digest_info_prefix(hash);
+    let digest_info_len = prefix.len() + hash.digest_size();
+    if k < digest_info_len + 11 {
+        return Err(Error::KeyTooSmall);
+    }
+
+    let mut encoded = Vec::with_capacity(k);
+    encoded.extend_from_slice(&[0x00, 0x01]);
+    encoded.resize(k - digest_info_len - 1, 0xff);
+    encoded.push(0x00);
+    encoded.extend_from_slice(prefix);
+    encoded.extend_from_slice(&hash.digest(message));
+    Ok(encoded)
+}
+
+/// Hashes `0x00 * 8 || mHash || salt`, the value `H` that an EMSA-PSS encoding commits to.
+fn pss_commitment(hash: HashAlg, message_hash: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut prefixed = vec![0u8; 8];
+    prefixed.extend_from_slice(message_hash);
+    prefixed.extend_from_slice(salt);
+    hash.digest(&prefixed)
+}
+
+/// The `emsa_pss_encode` function encodes a message for an RSASSA-PSS signature (RFC 8017 §9.1.1):
+/// `maskedDB || H || 0xbc`, where `H` commits to the digest and a random salt and `DB` carries the
+/// salt, masked with MGF1 over `H`. MGF1 uses the same hash function as the message.
+///
+/// # Arguments
+///
+/// * `message` - The message to be signed.
+/// * `hash` - The hash function to digest the message with.
+/// * `salt_len` - The length of the salt in bytes, usually the digest size.
+/// * `em_bits` - The maximal bit length of the encoding, one less than the bit length of the modulus.
+/// * `rng` - The random number generator used to draw the salt.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the `ceil(em_bits / 8)` byte encoding, or `Error::KeyTooSmall`
+///   if it cannot hold the digest and the salt.
+pub fn emsa_pss_encode<R: RngCore + CryptoRng + ?Sized>(
+    message: &[u8],
+    hash: HashAlg,
+    salt_len: usize,
+    em_bits: usize,
+    rng: &mut R,
+) -> Result<Vec<u8>, Error> {
+    let h_len = hash.digest_size();
+    let em_len = em_bits.div_ceil(8);
+    if em_len < h_len + salt_len + 2 {
+        return Err(Error::KeyTooSmall);
+    }
+
+    let mut salt = vec![0u8; salt_len];
+    rng.fill_bytes(&mut salt);
+    let commitment = pss_commitment(hash, &hash.digest(message), &salt);
+
+    let mut db = vec![0u8; em_len - salt_len - h_len - 2];
+    db.push(0x01);
+    db.extend_from_slice(&salt);
+    xor_in_place(&mut db, &mgf1(hash, &commitment, em_len - h_len - 1));
+    // Clear the bits above em_bits, so the encoding is smaller than the modulus
+    db[0] &= 0xffu8 >> (8 * em_len - em_bits);
+
+    let mut encoded = db;
+    encoded.extend_from_slice(&commitment);
+    encoded.push(PSS_TRAILER);
+    Ok(encoded)
+}
+
+/// The `emsa_pss_verify` function checks an EMSA-PSS encoding (RFC 8017 §9.1.2). The salt length is
+/// not needed, as it is recovered from the position of the `0x01` byte in `DB`.
+///
+/// # Arguments
+///
+/// * `message` - The signed message.
+/// * `encoded` - The encoding, `ceil(em_bits / 8)` bytes.
+/// * `hash` - The hash function the message was signed with.
+/// * `em_bits` - The maximal bit length of the encoding.
+///
+/// # Returns
+///
+/// * `Result<usize, Error>` - Returns the salt length, or `Error::InvalidSignature` if the encoding is not
+///   consistent with the message.
+pub fn emsa_pss_verify(message: &[u8], encoded: &[u8], hash: HashAlg, em_bits: usize) -> Result<usize, Error> {
+    let h_len = hash.digest_size();
+    let em_len = em_bits.div_ceil(8);
+    if encoded.len() != em_len || em_len < h_len + 2 || encoded[em_len - 1] != PSS_TRAILER {
+        return Err(Error::InvalidSignature);
+    }
+
+    let (masked_db, rest) = encoded.split_at(em_len - h_len - 1);
+    let commitment = &rest[..h_len];
+    let top_bits = !(0xffu8 >> (8 * em_len - em_bits));
+    if masked_db[0] & top_bits != 0 {
+        return Err(Error::InvalidSignature);
+    }
+
+    let mut db = masked_db.to_vec();
+    xor_in_place(&mut db, &mgf1(hash, commitment, masked_db.len()));
+    db[0] &= !top_bits;
+
+    // DB is zeros, 0x01 and the salt, so the first nonzero byte ends the padding
+    let separator = db.iter().position(|&b| b != 0).ok_or(Error::InvalidSignature)?;
+    if db[separator] != 0x01 {
+        return Err(Error::InvalidSignature);
+    }
+    let salt = &db[separator + 1..];
+
+    if pss_commitment(hash, &hash.digest(message), salt) != commitment {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(salt.len())
+}
+
+impl RSA {
+    /// The `sign` function signs a message with RSASSA-PKCS1-v1_5 (RFC 8017 §8.2.1). The signature is
+    /// deterministic, so it matches `openssl dgst -sign` byte for byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to sign.
+    /// * `hash` - The hash function to digest the message with.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the signature as `k` big-endian bytes, or `Error::KeyTooSmall`
+    ///   if the modulus cannot hold the encoded digest.
+    pub fn sign(&self, message: &[u8], hash: HashAlg) -> Result<Vec<u8>, Error> {
+        let k = self.public_key.byte_len();
+        let encoded = emsa_pkcs1_v15_encode(message, hash, k)?;
+        let signature = self.private_key().decrypt(&BigUint::from_bytes_be(&encoded));
+        Ok(to_fixed_bytes(&signature, k))
+    }
+
+    /// The `sign_pss` function signs a message with RSASSA-PSS (RFC 8017 §8.1.1) and a fresh random salt,
+    /// so signing the same message twice gives different signatures.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to sign.
+    /// * `hash` - The hash function to digest the message with, also used by MGF1.
+    /// * `salt_len` - The length of the salt in bytes, usually `hash.digest_size()`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the signature as `k` big-endian bytes, or `Error::KeyTooSmall`
+    ///   if the modulus cannot hold the digest and the salt.
+    pub fn sign_pss(&self, message: &[u8], hash: HashAlg, salt_len: usize) -> Result<Vec<u8>, Error> {
+        self.sign_pss_with_rng(message, hash, salt_len, &mut thread_rng())
+    }
+
+    /// The `sign_pss_with_rng` function signs a message like `sign_pss`, drawing the salt from the given
+    /// random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to sign.
+    /// * `hash` - The hash function to digest the message with, also used by MGF1.
+    /// * `salt_len` - The length of the salt in bytes.
+    /// * `rng` - The random number generator used to draw the salt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<u8>, Error>` - Returns the signature, or an error as described for `sign_pss`.
+    pub fn sign_pss_with_rng<R: RngCore + CryptoRng + ?Sized>(
+        &self,
+        message: &[u8],
+        hash: HashAlg,
+        salt_len: usize,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, Error> {
+        let em_bits = (self.public_key.n.bits() as usize).checked_sub(1).ok_or(Error::KeyTooSmall)?;
+        let encoded = emsa_pss_encode(message, hash, salt_len, em_bits, rng)?;
+        let signature = self.private_key().decrypt(&BigUint::from_bytes_be(&encoded));
+        Ok(to_fixed_bytes(&signature, self.public_key.byte_len()))
+    }
+}
+
+impl PublicKey {
+    /// The `verify` function checks an RSASSA-PKCS1-v1_5 signature (RFC 8017 §8.2.2) by re-encoding the
+    /// message and comparing it with the encoding recovered from the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The signed message.
+    /// * `signature` - The signature, `k` big-endian bytes.
+    /// * `hash` - The hash function the message was signed with.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Returns `Ok` if the signature is valid, `Error::InvalidSignature` if it is
+    ///   not, or `Error::KeyTooSmall` if the modulus cannot hold the encoded digest.
+    pub fn verify(&self, message: &[u8], signature: &[u8], hash: HashAlg) -> Result<(), Error> {
+        let k = self.byte_len();
+        let expected = emsa_pkcs1_v15_encode(message, hash, k)?;
+        if signature.len() != k {
+            return Err(Error::InvalidSignature);
+        }
+
+        let encoded = self.encrypt(&BigUint::from_bytes_be(signature), self).map_err(|_| Error::InvalidSignature)?;
+        if to_fixed_bytes(&encoded, k) != expected {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// The `verify_pss` function checks an RSASSA-PSS signature (RFC 8017 §8.1.2) made with any salt
+    /// length.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The signed message.
+    /// * `signature` - The signature, `k` big-endian bytes.
+    /// * `hash` - The hash function the message was signed with, also used by MGF1.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, Error>` - Returns the salt length the signature was made with,
+    ///   `Error::InvalidSignature` if it is not valid, or `Error::KeyTooSmall` if the modulus is zero.
+    pub fn verify_pss(&self, message: &[u8], signature: &[u8], hash: HashAlg) -> Result<usize, Error> {
+        // The encoding is a byte shorter than the modulus when its bit length is a multiple of eight
+        let em_bits = (self.n.bits() as usize).checked_sub(1).ok_or(Error::KeyTooSmall)?;
+        let k = self.byte_len();
+        if signature.len() != k {
+            return Err(Error::InvalidSignature);
+        }
+        let encoded = self.encrypt(&BigUint::from_bytes_be(signature), self).map_err(|_| Error::InvalidSignature)?;
+        let encoded = to_fixed_bytes(&encoded, k);
+        let (high, encoded) = encoded.split_at(k - em_bits.div_ceil(8));
+        if high.iter().any(|&b| b != 0) {
+            return Err(Error::InvalidSignature);
+        }
+        emsa_pss_verify(message, encoded, hash, em_bits)
+    }
+}
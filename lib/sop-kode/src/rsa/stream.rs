@@ -0,0 +1,269 @@
+use std::io::{self, Read, Write};
+
+use num_bigint::BigUint;
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use crate::Error;
+use super::container::{decode_trailer, encode_trailer, Header, HEADER_SIZE, TRAILER_SIZE};
+use super::encryption::PublicKey;
+use super::keys::RSA;
+use super::padding::PaddingScheme;
+use super::utils::{frame_tail, framed_len, to_fixed_bytes, LENGTH_SUFFIX_SIZE};
+
+fn io_error(error: io::Error) -> Error {
+    Error::Io(error.kind())
+}
+
+/// Reads until the buffer is full or the input ends, and returns how many bytes were read.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(io_error(error)),
+        }
+    }
+    Ok(filled)
+}
+
+/// Writes a block as `k` big-endian bytes.
+fn write_block<W: Write>(writer: &mut W, block: &BigUint, block_size: usize) -> Result<(), Error> {
+    writer.write_all(&to_fixed_bytes(block, block_size)).map_err(io_error)
+}
+
+/// The `Decryptor` decrypts the blocks of one container in order. It holds back the decrypted bytes
+/// that may still turn out to be fill or the length suffix, so it needs at most one chunk plus the
+/// suffix of memory whatever the message length.
+pub(crate) struct Decryptor<'a> {
+    rsa: &'a RSA,
+    padding: PaddingScheme,
+    chunk_size: usize,
+    blocks: u64,
+    written: u64,
+    held: Vec<u8>,
+}
+
+impl<'a> Decryptor<'a> {
+    /// Checks the container header against the key and binds the OAEP label.
+    pub(crate) fn new(rsa: &'a RSA, header: &Header, label: &[u8]) -> Result<Self, Error> {
+        if header.fingerprint != rsa.public_key.fingerprint() {
+            return Err(Error::WrongKey);
+        }
+        if header.block_size != rsa.public_key.byte_len() {
+            return Err(Error::InvalidCiphertext("block size does not match the key"));
+        }
+
+        // The id records whether there was a label, so a missing or unexpected one is caught up front
+        let padding = PaddingScheme::from_id(header.padding_id)
+            .ok_or(Error::InvalidCiphertext("unknown padding scheme"))?
+            .with_label(label);
+        if padding.id() != header.padding_id || padding.label() != label {
+            return Err(Error::LabelMismatch);
+        }
+
+        Ok(Decryptor {
+            rsa,
+            chunk_size: rsa.public_key.chunk_size(&padding)?,
+            padding,
+            blocks: 0,
+            written: 0,
+            held: Vec::new(),
+        })
+    }
+
+    /// Decrypts a block into a whole chunk. Blocks are independent, so this can run in any order.
+    pub(crate) fn decrypt_block(&self, block: &BigUint) -> Result<Vec<u8>, Error> {
+        if block >= &self.rsa.public_key.n {
+            return Err(Error::InvalidCiphertext("block is not smaller than the modulus"));
+        }
+        let decrypted = self.rsa.private_key().decrypt(block);
+        let decrypted = to_fixed_bytes(&decrypted, self.rsa.public_key.byte_len());
+        self.padding.decode_chunk(&decrypted, self.chunk_size)
+    }
+
+    /// Takes the next decrypted chunk in order and writes the bytes that are certainly message bytes.
+    pub(crate) fn push_chunk<W: Write>(&mut self, chunk: &[u8], writer: &mut W) -> Result<(), Error> {
+        self.held.extend_from_slice(chunk);
+        self.blocks += 1;
+
+        // The fill is shorter than a chunk, so anything before the last chunk and suffix is message
+        let keep = self.chunk_size + LENGTH_SUFFIX_SIZE;
+        if self.held.len() > keep {
+            let ready = self.held.len() - keep;
+            writer.write_all(&self.held[..ready]).map_err(io_error)?;
+            self.held.drain(..ready);
+            self.written += ready as u64;
+        }
+        Ok(())
+    }
+
+    /// Decrypts the next block and writes the bytes that are certainly message bytes.
+    pub(crate) fn update<W: Write>(&mut self, block: &BigUint, writer: &mut W) -> Result<(), Error> {
+        let chunk = self.decrypt_block(block)?;
+        self.push_chunk(&chunk, writer)
+    }
+
+    /// Checks the trailer against the decrypted blocks and writes the rest of the message.
+    pub(crate) fn finish<W: Write>(self, block_count: u64, length: u64, writer: &mut W) -> Result<(), Error> {
+        if block_count != self.blocks {
+            return Err(Error::InvalidCiphertext("blocks do not match the block count"));
+        }
+        if framed_len(length, self.chunk_size) != self.blocks as u128 * self.chunk_size as u128 {
+            return Err(Error::InvalidCiphertext("block count does not match the length"));
+        }
+
+        let (rest, suffix) = self.held.split_at(self.held.len() - LENGTH_SUFFIX_SIZE);
+        if u64::from_be_bytes(suffix.try_into().unwrap()) != length {
+            return Err(Error::InvalidFraming);
+        }
+        let remaining = length.checked_sub(self.written).ok_or(Error::InvalidFraming)?;
+        if remaining > rest.len() as u64 {
+            return Err(Error::InvalidFraming);
+        }
+        let (message, fill) = rest.split_at(remaining as usize);
+        if fill.iter().any(|&b| b != 0) {
+            return Err(Error::InvalidFraming);
+        }
+        writer.write_all(message).map_err(io_error)
+    }
+}
+
+impl PublicKey {
+    /// The `encrypt_stream` function encrypts everything the reader yields to the owner of this key with
+    /// PKCS#1 v1.5 padding and writes the ciphertext container as it goes, so memory use does not grow
+    /// with the input.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The plaintext source.
+    /// * `writer` - The container destination. Wrap files in a `BufWriter`, as blocks are written one by one.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, Error>` - Returns the number of plaintext bytes encrypted, `Error::KeyTooSmall` if the
+    ///   modulus cannot hold a chunk, or `Error::Io` if reading or writing fails.
+    pub fn encrypt_stream<R: Read, W: Write>(&self, reader: R, writer: W) -> Result<u64, Error> {
+        self.encrypt_stream_with_rng(reader, writer, &PaddingScheme::default(), &mut thread_rng())
+    }
+
+    /// The `encrypt_stream_with_rng` function encrypts a stream like `encrypt_stream`, with the given
+    /// padding scheme and source of padding randomness. It writes the same container as
+    /// `encrypt_bytes_with_rng` given the same generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The plaintext source.
+    /// * `writer` - The container destination.
+    /// * `padding` - The padding scheme to encrypt the chunks with.
+    /// * `rng` - The random number generator used to draw the padding bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, Error>` - Returns the number of plaintext bytes encrypted, or an error as described
+    ///   for `encrypt_stream`.
+    pub fn encrypt_stream_with_rng<R: Read, W: Write, G: RngCore + CryptoRng + ?Sized>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        padding: &PaddingScheme,
+        rng: &mut G,
+    ) -> Result<u64, Error> {
+        let chunk_size = self.chunk_size(padding)?;
+        let block_size = self.byte_len();
+        let header = Header { padding_id: padding.id(), fingerprint: self.fingerprint(), block_size };
+        writer.write_all(&header.encode()).map_err(io_error)?;
+
+        let mut encrypt_chunk = |chunk: &[u8], writer: &mut W| {
+            let encoded = padding.encode_chunk(chunk, block_size, rng)?;
+            write_block(writer, &self.encrypt(&BigUint::from_bytes_be(&encoded), self)?, block_size)
+        };
+
+        let mut chunk = vec![0u8; chunk_size];
+        let mut length = 0u64;
+        let mut blocks = 0u64;
+        loop {
+            let filled = read_full(&mut reader, &mut chunk)?;
+            length += filled as u64;
+            if filled < chunk_size {
+                // The input ended, so the last bytes are framed with the total length
+                for framed in frame_tail(&chunk[..filled], length, chunk_size).chunks(chunk_size) {
+                    encrypt_chunk(framed, &mut writer)?;
+                    blocks += 1;
+                }
+                break;
+            }
+            encrypt_chunk(&chunk, &mut writer)?;
+            blocks += 1;
+        }
+
+        writer.write_all(&encode_trailer(blocks, length)).map_err(io_error)?;
+        writer.flush().map_err(io_error)?;
+        Ok(length)
+    }
+}
+
+impl RSA {
+    /// The `decrypt_stream` function decrypts a ciphertext container from the reader and writes the
+    /// plaintext as it goes, so memory use does not grow with the input. The trailer is only checked
+    /// at the end, so on an error the written output must be discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The container source.
+    /// * `writer` - The plaintext destination.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, Error>` - Returns the number of plaintext bytes written, or an error as described for
+    ///   `decrypt_bytes`, or `Error::Io` if reading or writing fails.
+    pub fn decrypt_stream<R: Read, W: Write>(&self, reader: R, writer: W) -> Result<u64, Error> {
+        self.decrypt_stream_with_label(reader, writer, &[])
+    }
+
+    /// The `decrypt_stream_with_label` function decrypts a stream like `decrypt_stream`, checking the
+    /// OAEP label the message was bound to.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The container source.
+    /// * `writer` - The plaintext destination.
+    /// * `label` - The label the message was encrypted with, empty if there was none.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64, Error>` - Returns the number of plaintext bytes written, or an error as described for
+    ///   `decrypt_bytes_with_label`, or `Error::Io` if reading or writing fails.
+    pub fn decrypt_stream_with_label<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        label: &[u8],
+    ) -> Result<u64, Error> {
+        let mut header = [0u8; HEADER_SIZE];
+        if read_full(&mut reader, &mut header)? < HEADER_SIZE {
+            return Err(Error::InvalidCiphertext("truncated"));
+        }
+        let header = Header::decode(&header)?;
+        let mut decryptor = Decryptor::new(self, &header, label)?;
+
+        // A block is only decrypted once a whole trailer follows it, so the trailer is never taken for a block
+        let block_size = header.block_size;
+        let mut pending = vec![0u8; block_size + TRAILER_SIZE];
+        let mut filled = read_full(&mut reader, &mut pending)?;
+        while filled == pending.len() {
+            decryptor.update(&BigUint::from_bytes_be(&pending[..block_size]), &mut writer)?;
+            pending.copy_within(block_size.., 0);
+            filled = TRAILER_SIZE + read_full(&mut reader, &mut pending[TRAILER_SIZE..])?;
+        }
+        if filled != TRAILER_SIZE {
+            return Err(Error::InvalidCiphertext("truncated"));
+        }
+
+        let (block_count, length) = decode_trailer(pending[..TRAILER_SIZE].try_into().unwrap());
+        decryptor.finish(block_count, length, &mut writer)?;
+        writer.flush().map_err(io_error)?;
+        Ok(length)
+    }
+}
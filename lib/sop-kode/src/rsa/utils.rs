@@ -1,4 +1,5 @@
 use num_bigint::BigUint;
+use crate::Error;
 
 pub struct Estimation {
     time: f64,
@@ -36,7 +37,7 @@ pub enum TimeUnit {
 /// let number = base_n_to_base10(&digits, base);
 /// assert_eq!(number, 14); // 211 in base 3 is 22 in base 10
 /// ```
-pub fn base_n_to_base10(digits: &Vec<i64>, base: i64) -> i64 {
+pub fn base_n_to_base10(digits: &[i64], base: i64) -> i64 {
     digits.iter().rev().enumerate().fold(0, |acc, (i, &digit)| {
         if digit == -1 {
             acc
@@ -46,15 +47,29 @@ pub fn base_n_to_base10(digits: &Vec<i64>, base: i64) -> i64 {
     })
 }
 
+/// The `calculate_chunk_size` function calculates how many message bytes fit in one block for the
+/// modulus `n`: the largest byte count whose values are all smaller than `n`.
+///
+/// # Arguments
+///
+/// * `n` - The modulus of the recipient's key.
+///
+/// # Returns
+///
+/// * `usize` - Returns the chunk size in bytes, which is zero for moduli below 2^8.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use sop_kode::rsa::*;
+/// // A 1024-bit modulus holds any 127-byte value, but not every 128-byte value
+/// let n = BigUint::from(1u8) << 1023u32;
+/// assert_eq!(calculate_chunk_size(&n), 127);
+/// ```
 pub fn calculate_chunk_size(n: &BigUint) -> usize {
-    // Get the size of n in bytes
-    let n_size = n.bits() / 8;
-
-    // Subtract a few bytes to leave room for padding
-    //let padding = 11; // For PKCS#1 v1.5 padding
-    let chunk_size = n_size;
-
-    chunk_size as usize
+    // 2^(8 * size) must not exceed n, so the chunk gets at most bits(n) - 1 bits
+    (n.bits().saturating_sub(1) / 8) as usize
 }
 
 /// The `chunk_message` function splits a string into chunks of bytes of a specified size.
@@ -66,7 +81,8 @@ pub fn calculate_chunk_size(n: &BigUint) -> usize {
 ///
 /// # Returns
 ///
-/// * `Vec<Vec<u8>>` - A vector of byte vectors, where each inner vector represents a chunk of the original message.
+/// * `Result<Vec<Vec<u8>>, Error>` - A vector of byte vectors, where each inner vector represents a chunk of the original message,
+///   or `Error::InvalidChunkSize` if `chunk_size` is zero.
 ///
 /// # Example
 ///
@@ -74,23 +90,122 @@ pub fn calculate_chunk_size(n: &BigUint) -> usize {
 /// use sop_kode::rsa::*;
 /// let message = "This is a test message.";
 /// let chunk_size = 5;
-/// let chunks = chunk_message(message, chunk_size);
+/// let chunks = chunk_message(message, chunk_size).unwrap();
 /// ```
-pub fn chunk_message(s: &str, chunk_size: usize) -> Vec<Vec<u8>> {
-    let mut bytes: Vec<u8> = s.chars().map(|c| c as u8).collect();
+pub fn chunk_message(s: &str, chunk_size: usize) -> Result<Vec<Vec<u8>>, Error> {
+    chunk_bytes(s.as_bytes(), chunk_size)
+}
 
-    // Calculate the amount of padding needed
-    let padding = chunk_size - (bytes.len() % chunk_size);
+/// The `chunk_bytes` function splits bytes into chunks of a specified size, filling the last chunk
+/// with zero bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to be chunked.
+/// * `chunk_size` - The size of each chunk.
+///
+/// # Returns
+///
+/// * `Result<Vec<Vec<u8>>, Error>` - A vector of chunks, or `Error::InvalidChunkSize` if `chunk_size` is zero.
+pub fn chunk_bytes(bytes: &[u8], chunk_size: usize) -> Result<Vec<Vec<u8>>, Error> {
+    if chunk_size == 0 {
+        return Err(Error::InvalidChunkSize);
+    }
 
-    // Append all padding at once
-    bytes.resize(bytes.len() + padding, 0);
+    // Fill the last chunk up to a whole chunk
+    let mut bytes = bytes.to_vec();
+    bytes.resize(bytes.len().div_ceil(chunk_size) * chunk_size, 0);
 
     let chunks: Vec<Vec<u8>> = bytes
         .chunks(chunk_size)
         .map(|chunk| chunk.to_vec())
         .collect();
 
-    chunks
+    Ok(chunks)
+}
+
+
+/// The number of bytes in the big-endian length that ends every framed message.
+pub const LENGTH_SUFFIX_SIZE: usize = 8;
+
+/// The `framed_len` function calculates how many bytes a message of `length` bytes takes once framed:
+/// the message, the zero fill and the length suffix, rounded up to whole chunks.
+///
+/// # Arguments
+///
+/// * `length` - The message length in bytes.
+/// * `chunk_size` - The size of each chunk, which must not be zero.
+///
+/// # Returns
+///
+/// * `u128` - Returns the framed length in bytes.
+pub fn framed_len(length: u64, chunk_size: usize) -> u128 {
+    (length as u128 + LENGTH_SUFFIX_SIZE as u128).div_ceil(chunk_size as u128) * chunk_size as u128
+}
+
+/// The `frame_bytes` function appends zero fill and the message length, so the framed message fills whole
+/// chunks and the fill can be told apart from the message. The length comes last, so a stream can be
+/// framed once its end is reached.
+///
+/// # Arguments
+///
+/// * `bytes` - The message bytes.
+/// * `chunk_size` - The size of each chunk.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - Returns the bytes, the fill and the 64-bit big-endian length, or
+///   `Error::InvalidChunkSize` if `chunk_size` is zero.
+pub fn frame_bytes(bytes: &[u8], chunk_size: usize) -> Result<Vec<u8>, Error> {
+    if chunk_size == 0 {
+        return Err(Error::InvalidChunkSize);
+    }
+    Ok(frame_tail(bytes, bytes.len() as u64, chunk_size))
+}
+
+/// Frames the last bytes of a message whose earlier chunks were already written whole.
+pub(crate) fn frame_tail(tail: &[u8], length: u64, chunk_size: usize) -> Vec<u8> {
+    let framed_len = (tail.len() + LENGTH_SUFFIX_SIZE).div_ceil(chunk_size) * chunk_size;
+    let mut framed = tail.to_vec();
+    framed.resize(framed_len - LENGTH_SUFFIX_SIZE, 0);
+    framed.extend_from_slice(&length.to_be_bytes());
+    framed
+}
+
+/// The `unframe_bytes` function reverses `frame_bytes` on the decrypted chunks.
+///
+/// # Arguments
+///
+/// * `framed` - The message bytes, the zero fill and the length suffix.
+///
+/// # Returns
+///
+/// * `Result<&[u8], Error>` - Returns the message bytes, or `Error::InvalidFraming` if the length does not
+///   fit the data or the fill is not zero, which usually means the wrong key was used.
+pub fn unframe_bytes(framed: &[u8]) -> Result<&[u8], Error> {
+    if framed.len() < LENGTH_SUFFIX_SIZE {
+        return Err(Error::InvalidFraming);
+    }
+    let (rest, length) = framed.split_at(framed.len() - LENGTH_SUFFIX_SIZE);
+    let length = u64::from_be_bytes(length.try_into().unwrap());
+    if length > rest.len() as u64 {
+        return Err(Error::InvalidFraming);
+    }
+
+    let (message, fill) = rest.split_at(length as usize);
+    if fill.iter().any(|&b| b != 0) {
+        return Err(Error::InvalidFraming);
+    }
+    Ok(message)
+}
+
+/// Encodes an integer as exactly `length` big-endian bytes, restoring the leading zeros the integer
+/// conversion drops (I2OSP in RFC 8017 §4.1). The integer must fit.
+pub(crate) fn to_fixed_bytes(value: &BigUint, length: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut encoded = vec![0u8; length - bytes.len()];
+    encoded.extend_from_slice(&bytes);
+    encoded
 }
 
 
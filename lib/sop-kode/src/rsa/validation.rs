@@ -0,0 +1,88 @@
+use std::fmt;
+
+/// The ValidationFailure enum describes a single check that a key did not pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationFailure {
+    /// The public exponent is not greater than 1.
+    ExponentTooSmall,
+    /// The public exponent is even.
+    ExponentEven,
+    /// The public exponent is not smaller than the modulus.
+    ExponentTooLarge,
+    /// The modulus is even.
+    ModulusEven,
+    /// The modulus has fewer bits than the minimum key size.
+    ModulusTooSmall { bits: u64, min: u64 },
+    /// The prime `p` failed the Miller-Rabin test.
+    PNotPrime,
+    /// The prime `q` failed the Miller-Rabin test.
+    QNotPrime,
+    /// The product of `p` and `q` is not the modulus.
+    ModulusMismatch,
+    /// The primes do not each have half the bits of the modulus.
+    PrimeSizeMismatch { n_bits: u64, p_bits: u64, q_bits: u64 },
+    /// The private exponent is not the inverse of `e` modulo λ(n).
+    ExponentMismatch,
+    /// The private key holds no prime factors, so the checks on `p`, `q` and λ(n) could not run.
+    MissingPrimeFactors,
+    /// Encrypting and then decrypting a test message did not give the message back.
+    PairwiseConsistency,
+}
+
+impl fmt::Display for ValidationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationFailure::ExponentTooSmall => write!(f, "public exponent must be greater than 1"),
+            ValidationFailure::ExponentEven => write!(f, "public exponent must be odd"),
+            ValidationFailure::ExponentTooLarge => write!(f, "public exponent must be smaller than n"),
+            ValidationFailure::ModulusEven => write!(f, "modulus must be odd"),
+            ValidationFailure::ModulusTooSmall { bits, min } => {
+                write!(f, "modulus has {} bits, the minimum is {} bits", bits, min)
+            }
+            ValidationFailure::PNotPrime => write!(f, "p is not prime"),
+            ValidationFailure::QNotPrime => write!(f, "q is not prime"),
+            ValidationFailure::ModulusMismatch => write!(f, "n is not equal to p * q"),
+            ValidationFailure::PrimeSizeMismatch { n_bits, p_bits, q_bits } => write!(
+                f,
+                "p ({} bits) and q ({} bits) do not each have half of the {} bits of n",
+                p_bits, q_bits, n_bits
+            ),
+            ValidationFailure::ExponentMismatch => write!(f, "e * d is not congruent to 1 mod lambda(n)"),
+            ValidationFailure::MissingPrimeFactors => write!(f, "private key has no prime factors to check"),
+            ValidationFailure::PairwiseConsistency => write!(f, "pairwise encrypt/decrypt consistency test failed"),
+        }
+    }
+}
+
+/// The ValidationReport struct collects every check a key failed during validation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    failures: Vec<ValidationFailure>,
+}
+
+impl ValidationReport {
+    /// Records a failed check.
+    pub(crate) fn fail(&mut self, failure: ValidationFailure) {
+        self.failures.push(failure);
+    }
+
+    /// Returns `true` if the key passed every check.
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns the checks the key failed.
+    pub fn failures(&self) -> &[ValidationFailure] {
+        &self.failures
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "key is valid");
+        }
+        let failures: Vec<String> = self.failures.iter().map(|failure| failure.to_string()).collect();
+        write!(f, "key is invalid: {}", failures.join(", "))
+    }
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use sop_kode::aes::*;
+use sop_kode::Error;
+
+use common::hex;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example vectors of FIPS 197 Appendix C.
+    const FIPS_197_VECTORS: [(&str, &str); 3] = [
+        ("000102030405060708090a0b0c0d0e0f", "69c4e0d86a7b0430d8cdb78070b4c55a"),
+        ("000102030405060708090a0b0c0d0e0f1011121314151617", "dda97ca4864cdfe06eaf70a0ec0d7191"),
+        ("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f", "8ea2b7ca516745bfeafc49904b496089"),
+    ];
+
+    #[test]
+    fn encrypts_fips_197_vectors() {
+        for (key, expected) in FIPS_197_VECTORS {
+            let aes = Aes::new(&hex(key)).unwrap();
+            let mut block: [u8; BLOCK_SIZE] = hex("00112233445566778899aabbccddeeff").try_into().unwrap();
+            aes.encrypt_block(&mut block);
+            assert_eq!(block.to_vec(), hex(expected));
+        }
+    }
+
+    #[test]
+    fn decrypts_fips_197_vectors() {
+        for (key, ciphertext) in FIPS_197_VECTORS {
+            let aes = Aes::new(&hex(key)).unwrap();
+            let mut block: [u8; BLOCK_SIZE] = hex(ciphertext).try_into().unwrap();
+            aes.decrypt_block(&mut block);
+            assert_eq!(block.to_vec(), hex("00112233445566778899aabbccddeeff"));
+        }
+    }
+
+    #[test]
+    fn cbc_matches_sp800_38a_vector() {
+        // NIST SP 800-38A F.2.5, CBC-AES256.Encrypt, first block
+        let key = hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+        let iv: [u8; BLOCK_SIZE] = hex("000102030405060708090a0b0c0d0e0f").try_into().unwrap();
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172a");
+
+        let ciphertext = cbc_encrypt(&key, &iv, &plaintext).unwrap();
+        assert_eq!(ciphertext[..BLOCK_SIZE].to_vec(), hex("f58c4c04d6e5f1ba779eabfb5f7bfbd6"));
+        // The second block is the PKCS#7 padding
+        assert_eq!(ciphertext.len(), 2 * BLOCK_SIZE);
+    }
+
+    #[test]
+    fn cbc_round_trips_every_length() {
+        let key = [7u8; 32];
+        let iv = [9u8; BLOCK_SIZE];
+        for length in 0..40 {
+            let plaintext: Vec<u8> = (0..length as u8).collect();
+            let ciphertext = cbc_encrypt(&key, &iv, &plaintext).unwrap();
+            assert_eq!(cbc_decrypt(&key, &iv, &ciphertext).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn cbc_rejects_bad_padding_and_lengths() {
+        let key = [7u8; 16];
+        let iv = [9u8; BLOCK_SIZE];
+        let ciphertext = cbc_encrypt(&key, &iv, b"Hej med dig").unwrap();
+
+        assert_eq!(cbc_decrypt(&[8u8; 16], &iv, &ciphertext).err(), Some(Error::InvalidPadding));
+        assert_eq!(cbc_decrypt(&key, &iv, &ciphertext[..15]).err(), Some(Error::InvalidPadding));
+        assert_eq!(Aes::new(&[0u8; 20]).err(), Some(Error::InvalidKeyLength));
+    }
+}
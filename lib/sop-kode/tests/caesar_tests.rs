@@ -1,4 +1,4 @@
-use sop_kode::caesar_shift;
+use sop_kode::{caesar_shift, Error};
 
 #[cfg(test)]
 mod tests {
@@ -11,7 +11,7 @@ mod tests {
             "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q",
             "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
         ];
-        let encrypted = caesar_shift(plaintext, 3, alphabet);
+        let encrypted = caesar_shift(plaintext, 3, alphabet).unwrap();
         assert_eq!(encrypted, "KHOOR");
     }
 
@@ -22,7 +22,7 @@ mod tests {
             "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q",
             "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
         ];
-        let encrypted = caesar_shift(plaintext, 3, alphabet);
+        let encrypted = caesar_shift(plaintext, 3, alphabet).unwrap();
         assert_eq!(encrypted, "KHOORZRUOG");
     }
 
@@ -33,7 +33,7 @@ mod tests {
             "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q",
             "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
         ];
-        let encrypted = caesar_shift(plaintext, 3, alphabet);
+        let encrypted = caesar_shift(plaintext, 3, alphabet).unwrap();
         assert_eq!(encrypted, "");
     }
 
@@ -44,7 +44,18 @@ mod tests {
             "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q",
             "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
         ];
-        let encrypted = caesar_shift(plaintext, 0, alphabet);
+        let encrypted = caesar_shift(plaintext, 0, alphabet).unwrap();
         assert_eq!(encrypted, "HELLO");
     }
+
+    #[test]
+    fn caesar_shift_rejects_unknown_symbol() {
+        let plaintext = "HELLO!";
+        let alphabet = vec![
+            "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q",
+            "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+        ];
+        let encrypted = caesar_shift(plaintext, 3, alphabet);
+        assert_eq!(encrypted, Err(Error::UnknownSymbol('!')));
+    }
 }
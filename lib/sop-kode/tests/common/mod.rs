@@ -0,0 +1,41 @@
+//! Helpers shared by the integration tests.
+
+// Every test crate that includes this module uses only some of the helpers
+#![allow(dead_code)]
+
+use rand::{CryptoRng, RngCore};
+
+/// Decodes a hex string into bytes.
+pub fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+/// Replays fixed bytes as randomness, so a known-answer test can inject an OAEP seed or a PSS salt.
+pub struct FixedRng(pub Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(dest.len() <= self.0.len(), "ran out of fixed randomness");
+        dest.copy_from_slice(&self.0[..dest.len()]);
+        self.0.drain(..dest.len());
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
@@ -0,0 +1,187 @@
+mod common;
+
+use sop_kode::hash::*;
+
+use common::hex;
+
+/// The two-block message of the FIPS 180-4 examples for SHA-1 and SHA-256 (448 bits).
+const TWO_BLOCK_MESSAGE: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+/// The two-block message of the FIPS 180-4 examples for SHA-512 (896 bits).
+const TWO_BLOCK_MESSAGE_512: &[u8] =
+    b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+/// Hashes one million `a`s in uneven pieces, the long message of the NIST examples.
+fn million_a<H>(mut hasher: H, update: impl Fn(&mut H, &[u8])) -> H {
+    let piece = [b'a'; 999];
+    for _ in 0..1001 {
+        update(&mut hasher, &piece);
+    }
+    update(&mut hasher, &piece[..1]);
+    hasher
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod sha1_tests {
+        use super::*;
+
+        #[test]
+        fn matches_fips_180_4_examples() {
+            assert_eq!(sha1(b"abc").to_vec(), hex("a9993e364706816aba3e25717850c26c9cd0d89d"));
+            assert_eq!(sha1(b"").to_vec(), hex("da39a3ee5e6b4b0d3255bfef95601890afd80709"));
+            assert_eq!(sha1(TWO_BLOCK_MESSAGE).to_vec(), hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1"));
+        }
+
+        #[test]
+        fn hashes_one_million_a() {
+            let digest = million_a(Sha1::new(), Sha1::update).finalize();
+            assert_eq!(digest.to_vec(), hex("34aa973cd4c4daa4f61eeb2bdbad27316534016f"));
+        }
+
+        #[test]
+        fn incremental_updates_match_single_update() {
+            let message: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+            for split in [0, 1, 55, 56, 63, 64, 65, 999, 1000] {
+                let mut hasher = Sha1::new();
+                hasher.update(&message[..split]);
+                hasher.update(&message[split..]);
+                assert_eq!(hasher.finalize(), sha1(&message), "split at {}", split);
+            }
+        }
+    }
+
+    mod sha256_tests {
+        use super::*;
+
+        #[test]
+        fn matches_fips_180_4_two_block_example() {
+            assert_eq!(
+                sha256(TWO_BLOCK_MESSAGE).to_vec(),
+                hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+            );
+        }
+
+        #[test]
+        fn hashes_one_million_a() {
+            let digest = million_a(Sha256::new(), Sha256::update).finalize();
+            assert_eq!(digest.to_vec(), hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"));
+        }
+
+        #[test]
+        fn hashes_abc() {
+            assert_eq!(sha256(b"abc").to_vec(), hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+        }
+
+        #[test]
+        fn hashes_empty_message() {
+            assert_eq!(sha256(b"").to_vec(), hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+        }
+
+        #[test]
+        fn incremental_updates_match_single_update() {
+            let message: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+            for split in [0, 1, 55, 56, 63, 64, 65, 999, 1000] {
+                let mut hasher = Sha256::new();
+                hasher.update(&message[..split]);
+                hasher.update(&message[split..]);
+                assert_eq!(hasher.finalize(), sha256(&message), "split at {}", split);
+            }
+        }
+    }
+
+    mod sha512_tests {
+        use super::*;
+
+        #[test]
+        fn matches_fips_180_4_examples() {
+            assert_eq!(
+                sha512(b"abc").to_vec(),
+                hex("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                     2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")
+            );
+            assert_eq!(
+                sha512(b"").to_vec(),
+                hex("cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+                     47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e")
+            );
+            assert_eq!(
+                sha512(TWO_BLOCK_MESSAGE_512).to_vec(),
+                hex("8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+                     501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909")
+            );
+        }
+
+        #[test]
+        fn hashes_one_million_a() {
+            let digest = million_a(Sha512::new(), Sha512::update).finalize();
+            assert_eq!(
+                digest.to_vec(),
+                hex("e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb\
+                     de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b")
+            );
+        }
+
+        #[test]
+        fn incremental_updates_match_single_update() {
+            let message: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+            for split in [0, 1, 111, 112, 127, 128, 129, 999, 1000] {
+                let mut hasher = Sha512::new();
+                hasher.update(&message[..split]);
+                hasher.update(&message[split..]);
+                assert_eq!(hasher.finalize(), sha512(&message), "split at {}", split);
+            }
+        }
+    }
+
+    mod hash_alg_tests {
+        use super::*;
+
+        #[test]
+        fn digest_matches_the_hash_functions() {
+            for (hash, digest) in [
+                (HashAlg::Sha1, sha1(b"abc").to_vec()),
+                (HashAlg::Sha256, sha256(b"abc").to_vec()),
+                (HashAlg::Sha512, sha512(b"abc").to_vec()),
+            ] {
+                assert_eq!(hash.digest(b"abc"), digest);
+                assert_eq!(hash.digest_size(), digest.len());
+            }
+        }
+    }
+
+    mod hmac_tests {
+        use super::*;
+
+        #[test]
+        fn matches_rfc4231_test_case_2() {
+            let tag = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+            assert_eq!(tag.to_vec(), hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"));
+        }
+
+        #[test]
+        fn matches_rfc4231_test_case_6() {
+            let tag = hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First");
+            assert_eq!(tag.to_vec(), hex("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"));
+        }
+    }
+
+    mod pbkdf2_tests {
+        use super::*;
+
+        #[test]
+        fn matches_rfc7914_test_vectors() {
+            assert_eq!(
+                pbkdf2_hmac_sha256(b"passwd", b"salt", 1, 64),
+                hex("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+                     49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783")
+            );
+            assert_eq!(
+                pbkdf2_hmac_sha256(b"Password", b"NaCl", 80000, 64),
+                hex("4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
+                     a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d")
+            );
+        }
+    }
+}
@@ -1,11 +1,73 @@
+mod common;
+
+use std::fs;
+
 use num_bigint::{BigInt, BigUint};
 
 use sop_kode::rsa::*;
 
+/// Loads the private key of the OpenSSL fixture key pair.
+fn openssl_key() -> RSA {
+    match load_key("tests/fixtures/openssl_rsa_2048.pem").unwrap() {
+        Key::Private(rsa) => rsa,
+        Key::Public(_) => panic!("expected a private key"),
+    }
+}
+
+/// Decrypts a raw block into its padded `k` byte encoding, like `openssl pkeyutl -decrypt -pkeyopt rsa_padding_mode:none`.
+fn decrypt_block(rsa: &RSA, block: &[u8]) -> Vec<u8> {
+    let decrypted = rsa.private_key().decrypt(&BigUint::from_bytes_be(block)).to_bytes_be();
+    let mut padded_message = vec![0u8; block.len() - decrypted.len()];
+    padded_message.extend_from_slice(&decrypted);
+    padded_message
+}
+
+/// Returns the factors of a strong pseudoprime to every prime base up to 97, built with Arnault's
+/// method as `p * (101 * (p - 1) + 1) * (113 * (p - 1) + 1)`.
+fn strong_pseudoprime_factors() -> [BigUint; 3] {
+    let p = BigUint::parse_bytes(b"251549953293709336418939706608639573554281043", 10).unwrap();
+    let q = BigUint::from(101u32) * (&p - 1u32) + 1u32;
+    let r = BigUint::from(113u32) * (&p - 1u32) + 1u32;
+    [p, q, r]
+}
+
+/// The 1024-bit key of Example 1 in oaep-vect.txt, the RSAES-OAEP test vectors published with RFC 8017.
+const OAEP_EXAMPLE_1_N: &str = "a8b3b284af8eb50b387034a860f146c4919f318763cd6c5598c8ae4811a1e0ab\
+                                  c4c7e0b082d693a5e7fced675cf4668512772c0cbc64a742c6c630f533c8cc72\
+                                  f62ae833c40bf25842e984bb78bdbf97c0107d55bdb662f5c4e0fab9845cb514\
+                                  8ef7392dd3aaff93ae1e6b667bb3d4247616d4f5ba10d4cfd226de88d39f16fb";
+const OAEP_EXAMPLE_1_P: &str = "d32737e7267ffe1341b2d5c0d150a81b586fb3132bed2f8d5262864a9cb9f30a\
+                                  f38be448598d413a172efb802c21acf1c11c520c2f26a471dcad212eac7ca39d";
+const OAEP_EXAMPLE_1_Q: &str = "cc8853d1d54da630fac004f471f281c7b8982d8224a490edbeb33d3e3d5cc93c\
+                                  4765703d1dd791642f1f116a0dd852be2419b2af72bfe9a030e860b0288b5d77";
+/// The message, seed and ciphertext of Examples 1.1 and 1.2 in oaep-vect.txt, encrypted with SHA-1 and MGF1-SHA-1.
+const OAEP_EXAMPLE_1_ENCRYPTIONS: [(&str, &str, &str); 2] = [
+    (
+        "6628194e12073db03ba94cda9ef9532397d50dba79b987004afefe34",
+        "18b776ea21069d69776a33e96bad48e1dda0a5ef",
+        "354fe67b4a126d5d35fe36c777791a3f7ba13def484e2d3908aff722fad468fb\
+         21696de95d0be911c2d3174f8afcc201035f7b6d8e69402de5451618c21a535f\
+         a9d7bfc5b8dd9fc243f8cf927db31322d6e881eaa91a996170e657a05a266426\
+         d98c88003f8477c1227094a0d9fa1e8c4024309ce1ecccb5210035d47ac72e8a",
+    ),
+    (
+        "750c4047f547e8e41411856523298ac9bae245efaf1397fbe56f9dd5",
+        "0cc742ce4a9b7f32f951bcb251efd925fe4fe35f",
+        "640db1acc58e0568fe5407e5f9b701dff8c3c91e716c536fc7fcec6cb5b71c11\
+         65988d4a279e1577d730fc7a29932e3f00c81515236d8d8e31017a7a09df4352\
+         d904cdeb79aa583adcc31ea698a4c05283daba9089be5491f67c1a4ee48dc74b\
+         bbe6643aef846679b4cb395a352d5ed115912df696ffe0702932946d71492b44",
+    ),
+];
+
 #[cfg(test)]
 mod tests {
-    use num_bigint::ToBigUint;
     use num_traits::{FromPrimitive, One, Zero};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use sop_kode::rsa::encryption::{PrivateKey, PublicKey};
+    use sop_kode::rsa::padding::{mgf1, oaep_overhead, oaep_pad, oaep_unpad, pkcs1_pad, pkcs1_unpad, PKCS1_OVERHEAD};
+    use sop_kode::Error;
 
     use super::*;
 
@@ -23,6 +85,20 @@ mod tests {
             let composite = BigUint::from_i32(15).unwrap();
             assert!(!miller_rabin(&composite, 5));
         }
+
+        #[test]
+        fn random_bases_catch_strong_pseudoprime_to_small_prime_bases() {
+            let [p, q, r] = strong_pseudoprime_factors();
+            let n = &p * &q * &r;
+            let n_minus_one = &n - 1u32;
+
+            // Every fixed base up to 97 is a strong liar for n, so those bases alone would call it prime
+            for base in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97] {
+                assert_eq!(BigUint::from(base).modpow(&(&n_minus_one >> 1), &n), n_minus_one, "base {}", base);
+            }
+            assert!(!miller_rabin_with_rng(&n, 40, &mut ChaCha20Rng::seed_from_u64(14)));
+            assert!(!miller_rabin(&n, 40));
+        }
     }
 
     #[test]
@@ -47,29 +123,564 @@ mod tests {
 
     #[test]
     fn base_n_to_base10_returns_correct_value_for_base_2() {
-        assert_eq!(base_n_to_base10(&vec![1, 0, 1], 2), 5);
+        assert_eq!(base_n_to_base10(&[1, 0, 1], 2), 5);
     }
 
     #[test]
     fn base_n_to_base10_returns_correct_value_for_base_10() {
-        assert_eq!(base_n_to_base10(&vec![1, 2, 3], 10), 123);
+        assert_eq!(base_n_to_base10(&[1, 2, 3], 10), 123);
     }
 
     #[test]
     fn base_n_to_base10_returns_correct_value_for_base_16() {
-        assert_eq!(base_n_to_base10(&vec![1, 2, 3], 16), 291);
+        assert_eq!(base_n_to_base10(&[1, 2, 3], 16), 291);
     }
 
     #[test]
     fn base_n_to_base10_returns_correct_value_for_base_28() {
-        assert_eq!(base_n_to_base10(&vec![1, 0], 28), 28);
+        assert_eq!(base_n_to_base10(&[1, 0], 28), 28);
     }
 
     #[test]
     fn decrypt_returns_correct_value() {
         let ciphertext = BigUint::from(13u64);
-        let private_key = (BigUint::from(33u64), BigUint::from(7u64));
-        assert_eq!(decrypt(ciphertext, &private_key), BigUint::from(7u64));
+        let private_key = PrivateKey::new(BigUint::from(33u64), BigUint::from(7u64));
+        assert_eq!(private_key.decrypt(&ciphertext), BigUint::from(7u64));
+    }
+
+    mod crt_tests {
+        use super::*;
+
+        #[test]
+        fn crt_decrypt_returns_correct_value() {
+            // p = 3, q = 11, e = 3, d = 7
+            let private_key =
+                PrivateKey::from_primes(BigUint::from(3u64), BigUint::from(11u64), BigUint::from(7u64)).unwrap();
+            assert!(private_key.has_crt());
+            assert_eq!(private_key.decrypt(&BigUint::from(13u64)), BigUint::from(7u64));
+        }
+
+        #[test]
+        fn crt_and_non_crt_decrypt_agree() {
+            let user = RSA::new(1024).unwrap();
+            let crt_key = user.private_key();
+            let slow_key = crt_key.without_crt();
+            assert!(crt_key.has_crt());
+            assert!(!slow_key.has_crt());
+
+            for m in [0u64, 1, 2, 65537, u64::MAX] {
+                let ciphertext = user.public_key.encrypt(&BigUint::from(m), &user.public_key).unwrap();
+                assert_eq!(crt_key.decrypt(&ciphertext), BigUint::from(m));
+                assert_eq!(slow_key.decrypt(&ciphertext), BigUint::from(m));
+            }
+        }
+    }
+
+    mod blinding_tests {
+        use super::*;
+        use num_bigint::RandBigInt;
+        use rand::{CryptoRng, RngCore};
+        use sop_kode::hash::HashAlg;
+
+        /// Counts how often randomness is drawn, to see that every operation draws its own factor.
+        struct CountingRng {
+            inner: ChaCha20Rng,
+            draws: usize,
+        }
+
+        impl RngCore for CountingRng {
+            fn next_u32(&mut self) -> u32 {
+                self.draws += 1;
+                self.inner.next_u32()
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.draws += 1;
+                self.inner.next_u64()
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                self.draws += 1;
+                self.inner.fill_bytes(dest)
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                self.draws += 1;
+                self.inner.try_fill_bytes(dest)
+            }
+        }
+
+        impl CryptoRng for CountingRng {}
+
+        #[test]
+        fn keys_blind_by_default() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(90)).unwrap();
+            assert!(user.private_key().has_blinding());
+            assert!(openssl_key().private_key().has_blinding());
+            assert!(RSA::from_jwk(&user.to_jwk()).unwrap().private_key().has_blinding());
+
+            // Both copies keep the other setting
+            assert!(user.private_key().without_crt().has_blinding());
+            assert!(user.private_key().without_blinding().has_crt());
+            assert!(!user.private_key().without_blinding().has_blinding());
+
+            // A bare key has no public exponent to blind with
+            assert!(!PrivateKey::new(BigUint::from(33u64), BigUint::from(7u64)).has_blinding());
+            let blinded = PrivateKey::new(BigUint::from(33u64), BigUint::from(7u64)).with_blinding(BigUint::from(3u64)).unwrap();
+            assert_eq!(blinded.decrypt(&BigUint::from(13u64)), BigUint::from(7u64));
+        }
+
+        #[test]
+        fn blinding_rejects_moduli_without_a_factor_to_draw() {
+            for n in [0u64, 1, 2] {
+                let key = PrivateKey::new(BigUint::from(n), BigUint::one());
+                assert_eq!(key.with_blinding(BigUint::from(3u64)).err(), Some(Error::KeyTooSmall), "n = {}", n);
+            }
+            // The smallest modulus that can blind
+            let key = PrivateKey::new(BigUint::from(3u64), BigUint::one()).with_blinding(BigUint::from(3u64)).unwrap();
+            assert_eq!(key.decrypt(&BigUint::from(2u64)), BigUint::from(2u64));
+        }
+
+        #[test]
+        fn blinding_rejects_exponent_of_another_key() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(92)).unwrap();
+            let key = user.private_key().without_blinding();
+            assert!(key.clone().with_blinding(user.public_key.e.clone()).is_ok());
+
+            for e in [3u64, 17, 65539] {
+                let result = key.clone().with_blinding(BigUint::from(e));
+                assert_eq!(result.err(), Some(Error::ExponentMismatch), "e = {}", e);
+            }
+            let result = key.without_crt().with_blinding(&user.public_key.e + 2u32);
+            assert_eq!(result.err(), Some(Error::ExponentMismatch));
+        }
+
+        #[test]
+        fn blinded_and_unblinded_decryption_agree() {
+            let user = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(91)).unwrap();
+            let keys = [
+                user.private_key().clone(),
+                user.private_key().without_crt(),
+                user.private_key().without_blinding(),
+                user.private_key().without_crt().without_blinding(),
+            ];
+
+            let mut rng = ChaCha20Rng::seed_from_u64(92);
+            for _ in 0..8 {
+                let m = rng.gen_biguint_below(&user.public_key.n);
+                let ciphertext = user.public_key.encrypt(&m, &user.public_key).unwrap();
+                for key in &keys {
+                    assert_eq!(key.decrypt(&ciphertext), m);
+                }
+            }
+        }
+
+        #[test]
+        fn every_decryption_draws_a_fresh_factor() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(93)).unwrap();
+            let ciphertext = user.public_key.encrypt(&BigUint::from(42u64), &user.public_key).unwrap();
+            let mut rng = CountingRng { inner: ChaCha20Rng::seed_from_u64(94), draws: 0 };
+
+            assert_eq!(user.private_key().decrypt_with_rng(&ciphertext, &mut rng), BigUint::from(42u64));
+            let first = rng.draws;
+            assert!(first > 0);
+            assert_eq!(user.private_key().decrypt_with_rng(&ciphertext, &mut rng), BigUint::from(42u64));
+            assert!(rng.draws > first);
+
+            let before = rng.draws;
+            user.private_key().without_blinding().decrypt_with_rng(&ciphertext, &mut rng);
+            assert_eq!(rng.draws, before);
+        }
+
+        #[test]
+        fn signatures_and_decryption_do_not_depend_on_blinding() {
+            let user = openssl_key();
+            let unblinded = RSA::from_keys(user.public_key.clone(), user.private_key().without_blinding());
+            let message = b"Blinding changes nothing but the timing.";
+
+            assert_eq!(user.sign(message, HashAlg::Sha256).unwrap(), unblinded.sign(message, HashAlg::Sha256).unwrap());
+            let ciphertext = user.public_key.encrypt_bytes(message).unwrap();
+            assert_eq!(unblinded.decrypt_bytes(&ciphertext).unwrap(), message);
+            assert_eq!(user.decrypt_bytes(&ciphertext).unwrap(), message);
+        }
+    }
+
+    mod montgomery_tests {
+        use super::*;
+        use num_bigint::RandBigInt;
+        use sop_kode::rsa::math::Montgomery;
+
+        #[test]
+        fn matches_modpow_for_small_moduli() {
+            for n in (1u64..64).step_by(2) {
+                let modulus = BigUint::from(n);
+                for base in 0..n + 2 {
+                    for exponent in 0..18u64 {
+                        let (base, exponent) = (BigUint::from(base), BigUint::from(exponent));
+                        assert_eq!(ct_modpow(&base, &exponent, &modulus), base.modpow(&exponent, &modulus), "{}^{} mod {}", base, exponent, n);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn matches_modpow_over_random_inputs() {
+            let mut rng = ChaCha20Rng::seed_from_u64(100);
+            for bits in [64, 65, 127, 128, 521, 1024, 2048] {
+                for _ in 0..4 {
+                    let modulus = rng.gen_biguint(bits) | BigUint::one() | (BigUint::one() << (bits - 1));
+                    let montgomery = Montgomery::new(&modulus).unwrap();
+                    for _ in 0..4 {
+                        // Bases past the modulus and exponents wider than it are allowed too
+                        let base = rng.gen_biguint(bits + 8);
+                        let exponent = rng.gen_biguint(bits + 70);
+                        assert_eq!(montgomery.modpow(&base, &exponent), base.modpow(&exponent, &modulus), "{} bits", bits);
+                    }
+                    // The edges of the exponent range
+                    let base = rng.gen_biguint_below(&modulus);
+                    let all_ones = (BigUint::one() << bits) - BigUint::one();
+                    for exponent in [BigUint::zero(), BigUint::one(), &modulus - BigUint::one(), all_ones] {
+                        assert_eq!(montgomery.modpow(&base, &exponent), base.modpow(&exponent, &modulus), "{} bits", bits);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn even_moduli_fall_back_to_modpow() {
+            assert!(Montgomery::new(&BigUint::from(100u64)).is_none());
+            assert!(Montgomery::new(&BigUint::zero()).is_none());
+            let modulus = BigUint::from(2u64).pow(70) * BigUint::from(3u64);
+            let base = BigUint::from(123456789u64);
+            let exponent = BigUint::from(65537u64);
+            assert_eq!(ct_modpow(&base, &exponent, &modulus), base.modpow(&exponent, &modulus));
+        }
+
+        #[test]
+        fn private_key_path_matches_modpow() {
+            let user = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(101)).unwrap();
+            let key = user.private_key();
+            let (p, q) = key.primes().unwrap();
+            let (dp, dq, _) = key.crt_values().unwrap();
+
+            let mut rng = ChaCha20Rng::seed_from_u64(102);
+            for _ in 0..4 {
+                let ciphertext = rng.gen_biguint_below(&user.public_key.n);
+                assert_eq!(ct_modpow(&ciphertext, dp, p), ciphertext.modpow(dp, p));
+                assert_eq!(ct_modpow(&ciphertext, dq, q), ciphertext.modpow(dq, q));
+                let expected = ciphertext.modpow(key.d(), &user.public_key.n);
+                assert_eq!(key.decrypt(&ciphertext), expected);
+                assert_eq!(key.without_crt().without_blinding().decrypt(&ciphertext), expected);
+            }
+        }
+    }
+
+    mod builder_tests {
+        use super::*;
+
+        #[test]
+        fn builds_with_custom_parameters() {
+            let user = RsaBuilder::new()
+                .bits(512)
+                .public_exponent(3)
+                .mr_rounds(40)
+                .rng(rand::thread_rng())
+                .build()
+                .unwrap();
+            let message = "Hello";
+
+            assert_eq!(user.public_key.e, BigUint::from(3u64));
+            let encrypted_message = user.encrypt_message(message, user.public_key.clone()).unwrap();
+            assert_eq!(user.decrypt_message(&encrypted_message).unwrap(), message);
+        }
+
+        #[test]
+        fn rejects_too_small_bit_size() {
+            let result = RsaBuilder::new().bits(16).build();
+            assert_eq!(result.err(), Some(Error::BitsTooSmall { bits: 16, min: 32 }));
+        }
+
+        #[test]
+        fn rejects_odd_bit_size() {
+            let result = RsaBuilder::new().bits(1025).build();
+            assert_eq!(result.err(), Some(Error::OddBitSize(1025)));
+        }
+
+        #[test]
+        fn rejects_even_public_exponent() {
+            let result = RsaBuilder::new().bits(512).public_exponent(4).build();
+            assert_eq!(result.err(), Some(Error::InvalidPublicExponent(BigUint::from(4u64))));
+        }
+
+        #[test]
+        fn rejects_zero_rounds() {
+            let result = RsaBuilder::new().bits(512).mr_rounds(0).build();
+            assert_eq!(result.err(), Some(Error::ZeroRounds));
+        }
+
+        #[test]
+        fn from_primes_rejects_exponent_not_coprime_to_phi() {
+            // phi = 6 * 10 = 60, which shares the factor 3 with e
+            let result = RSA::from_primes(BigUint::from(7u64), BigUint::from(11u64), BigUint::from(3u64));
+            assert_eq!(result.err(), Some(Error::NonInvertibleExponent));
+        }
+
+        #[test]
+        fn from_primes_rejects_equal_primes() {
+            let result = RSA::from_primes(BigUint::from(11u64), BigUint::from(11u64), BigUint::from(3u64));
+            assert_eq!(result.err(), Some(Error::PrimeCollision));
+        }
+    }
+
+    mod seeded_rng_tests {
+        use super::*;
+
+        /// The modulus generated by `RSA::new_with_rng(512, ..)` seeded with 2023.
+        const GOLDEN_N_2023: &str = "df9328de7b978d68c9b9b1c8f09a6940cd72b6c3c311554d3d62203d5dcb346e\
+                                     2ff8131ca22b7e31fde5fdee7db69d1e791cf2074caf8e2592131c4b3319c2ef";
+
+        #[test]
+        fn generate_prime_is_reproducible() {
+            let mut rng = ChaCha20Rng::seed_from_u64(2023);
+            assert_eq!(generate_prime(64, &mut rng), Ok(BigUint::from(18076396970088901273u64)));
+        }
+
+        #[test]
+        fn new_with_rng_matches_golden_key() {
+            let mut rng = ChaCha20Rng::seed_from_u64(2023);
+            let user = RSA::new_with_rng(512, &mut rng).unwrap();
+            assert_eq!(user.public_key.n, BigUint::parse_bytes(GOLDEN_N_2023.as_bytes(), 16).unwrap());
+        }
+
+        #[test]
+        fn same_seed_gives_same_key_pair() {
+            let first = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+            let second = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+            let ciphertext = first.public_key.encrypt(&BigUint::from(42u64), &first.public_key).unwrap();
+
+            assert_eq!(first.public_key.n, second.public_key.n);
+            assert_eq!(second.private_key().decrypt(&ciphertext), BigUint::from(42u64));
+        }
+
+        #[test]
+        fn builder_with_seeded_rng_is_reproducible() {
+            let build = || RsaBuilder::new().bits(512).rng(ChaCha20Rng::seed_from_u64(11)).build().unwrap();
+            assert_eq!(build().public_key.n, build().public_key.n);
+        }
+
+        #[test]
+        fn pkcs1_pad_is_reproducible() {
+            let message = 1234u64.to_be_bytes();
+            let first = pkcs1_pad(&message, 64, &mut ChaCha20Rng::seed_from_u64(3)).unwrap();
+            let second = pkcs1_pad(&message, 64, &mut ChaCha20Rng::seed_from_u64(3)).unwrap();
+            assert_eq!(first, second);
+        }
+    }
+
+    mod key_size_tests {
+        use super::*;
+
+        #[test]
+        fn generate_prime_has_exact_bit_length() {
+            let mut rng = ChaCha20Rng::seed_from_u64(4);
+            for bits in [2, 3, 8, 17, 64, 128] {
+                assert_eq!(generate_prime(bits, &mut rng).unwrap().bits(), bits as u64);
+            }
+        }
+
+        #[test]
+        fn generate_prime_rejects_fewer_than_two_bits() {
+            let mut rng = ChaCha20Rng::seed_from_u64(5);
+            for bits in [0, 1] {
+                assert_eq!(generate_prime(bits, &mut rng), Err(Error::BitsTooSmall { bits, min: 2 }));
+                assert_eq!(generate_prime_with(bits, 3, &mut rng), Err(Error::BitsTooSmall { bits, min: 2 }));
+            }
+        }
+
+        #[test]
+        fn modulus_has_exact_bit_length_over_many_keys() {
+            let mut rng = ChaCha20Rng::seed_from_u64(5);
+            for bits in [32, 64, 128, 256] {
+                for _ in 0..50 {
+                    let user = RSA::new_with_rng(bits, &mut rng).unwrap();
+                    assert_eq!(user.public_key.n.bits(), bits as u64);
+                    assert_eq!(calculate_chunk_size(&user.public_key.n), bits / 8 - 1);
+                }
+            }
+        }
+
+        #[test]
+        fn new_has_exact_bit_length() {
+            let user = RSA::new(2048).unwrap();
+            assert_eq!(user.public_key.n.bits(), 2048);
+        }
+
+        #[test]
+        fn new_rejects_sizes_that_cannot_be_met() {
+            assert_eq!(RSA::new(1025).err(), Some(Error::OddBitSize(1025)));
+            assert_eq!(RSA::new(16).err(), Some(Error::BitsTooSmall { bits: 16, min: 32 }));
+            assert_eq!(
+                RSA::new_with_rng(513, &mut ChaCha20Rng::seed_from_u64(6)).err(),
+                Some(Error::OddBitSize(513))
+            );
+        }
+    }
+
+    mod error_tests {
+        use super::*;
+
+        #[test]
+        fn encrypt_rejects_message_not_smaller_than_modulus() {
+            let user = RSA::new(512).unwrap();
+            let message = user.public_key.n.clone();
+            assert_eq!(user.public_key.encrypt(&message, &user.public_key).err(), Some(Error::MessageTooLong));
+        }
+
+        #[test]
+        fn decrypt_message_with_wrong_key_does_not_panic() {
+            let sender = RSA::new(512).unwrap();
+            let recipient = RSA::new(512).unwrap();
+            let other = RSA::new(512).unwrap();
+            let message = "This message is meant for the recipient only, not for anybody else.";
+
+            let encrypted_message = sender.encrypt_message(message, recipient.public_key.clone()).unwrap();
+            assert_eq!(other.decrypt_message(&encrypted_message).err(), Some(Error::WrongKey));
+        }
+
+        #[test]
+        fn encrypt_message_rejects_key_too_small_for_a_chunk() {
+            let public_key = PublicKey { n: BigUint::from(253u32), e: BigUint::from(3u32) };
+            assert_eq!(public_key.encrypt_message("Hi").err(), Some(Error::KeyTooSmall));
+            assert_eq!(public_key.encrypt_bytes(b"Hi").err(), Some(Error::KeyTooSmall));
+
+            // A 64-bit modulus holds a textbook chunk but not the 11 bytes of PKCS#1 v1.5 padding
+            let public_key = RSA::new_with_rng(64, &mut ChaCha20Rng::seed_from_u64(17)).unwrap().public_key;
+            assert_eq!(public_key.encrypt_bytes(b"Hi").err(), Some(Error::KeyTooSmall));
+            assert_eq!(public_key.chunk_size(&PaddingScheme::Textbook), Ok(7));
+        }
+
+        #[test]
+        fn chunk_message_rejects_zero_chunk_size() {
+            assert_eq!(chunk_message("Hello", 0).err(), Some(Error::InvalidChunkSize));
+        }
+
+        #[test]
+        fn pkcs1_unpad_rejects_short_input() {
+            assert_eq!(pkcs1_unpad(&[0x00, 0x02, 0x2a, 0x00]).err(), Some(Error::InvalidPadding));
+        }
+
+        #[test]
+        fn private_key_from_equal_primes_is_rejected() {
+            let result = PrivateKey::from_primes(BigUint::from(11u64), BigUint::from(11u64), BigUint::from(3u64));
+            assert_eq!(result.err(), Some(Error::PrimeCollision));
+        }
+
+        #[test]
+        fn degenerate_primes_are_rejected() {
+            for (p, q) in [(0u64, 7u64), (1, 7), (7, 1), (4, 6)] {
+                let result = PrivateKey::from_primes(BigUint::from(p), BigUint::from(q), BigUint::from(3u64));
+                assert_eq!(result.err(), Some(Error::InvalidPrimeFactors), "p = {}, q = {}", p, q);
+            }
+            let result = RSA::from_primes(BigUint::one(), BigUint::from(11u64), BigUint::from(3u64));
+            assert_eq!(result.err(), Some(Error::InvalidPrimeFactors));
+        }
+    }
+
+    mod validation_tests {
+        use super::*;
+
+        #[test]
+        fn generated_key_is_valid() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(8)).unwrap();
+            let report = user.validate();
+            assert!(report.is_valid(), "{}", report);
+            assert!(user.public_key.validate().is_valid());
+        }
+
+        #[test]
+        fn public_key_reports_every_failed_check() {
+            let public_key = PublicKey { n: BigUint::from(1000u64), e: BigUint::from(1024u64) };
+            let report = public_key.validate();
+            assert_eq!(
+                report.failures(),
+                &[
+                    ValidationFailure::ExponentEven,
+                    ValidationFailure::ExponentTooLarge,
+                    ValidationFailure::ModulusEven,
+                    ValidationFailure::ModulusTooSmall { bits: 10, min: 32 },
+                ]
+            );
+        }
+
+        #[test]
+        fn public_key_rejects_exponent_of_one() {
+            let public_key = PublicKey { n: BigUint::from(0xC000_0001u64), e: BigUint::one() };
+            assert_eq!(public_key.validate().failures(), &[ValidationFailure::ExponentTooSmall]);
+        }
+
+        #[test]
+        fn detects_composite_prime() {
+            // p = 65535 = 3 * 5 * 17 * 257 is not prime
+            let p = BigUint::from(65535u64);
+            let q = BigUint::from(65537u64);
+            let n = &p * &q;
+            let public_key = PublicKey { n, e: BigUint::from(7u64) };
+            let private_key = PrivateKey::from_primes(p, q, BigUint::from(3u64)).unwrap();
+            let report = RSA::from_keys(public_key, private_key).validate();
+
+            assert!(!report.is_valid());
+            assert!(report.failures().contains(&ValidationFailure::PNotPrime));
+            assert!(!report.failures().contains(&ValidationFailure::QNotPrime));
+        }
+
+        #[test]
+        fn detects_strong_pseudoprime_factor() {
+            let [p, q, r] = strong_pseudoprime_factors();
+            let composite = &p * &q * &r;
+            let prime = generate_prime(composite.bits() as usize, &mut ChaCha20Rng::seed_from_u64(15)).unwrap();
+            let public_key = PublicKey { n: &composite * &prime, e: BigUint::from(65537u32) };
+            let private_key = PrivateKey::from_primes(composite, prime, BigUint::from(3u64)).unwrap();
+            let report = RSA::from_keys(public_key, private_key).validate();
+
+            assert!(report.failures().contains(&ValidationFailure::PNotPrime));
+            assert!(!report.failures().contains(&ValidationFailure::QNotPrime));
+        }
+
+        #[test]
+        fn detects_mismatched_private_exponent() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(9)).unwrap();
+            let (p, q) = user.private_key().primes().unwrap();
+            let wrong_d = user.private_key().d() + BigUint::one();
+            let private_key = PrivateKey::from_primes(p.clone(), q.clone(), wrong_d).unwrap();
+            let report = RSA::from_keys(user.public_key.clone(), private_key).validate();
+
+            assert_eq!(
+                report.failures(),
+                &[ValidationFailure::ExponentMismatch, ValidationFailure::PairwiseConsistency]
+            );
+        }
+
+        #[test]
+        fn detects_keys_that_do_not_belong_together() {
+            let first = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(10)).unwrap();
+            let second = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(11)).unwrap();
+            let report = RSA::from_keys(first.public_key.clone(), second.private_key().clone()).validate();
+
+            assert_eq!(
+                report.failures().iter().filter(|&failure| failure == &ValidationFailure::ModulusMismatch).count(),
+                1
+            );
+            assert!(report.failures().contains(&ValidationFailure::PairwiseConsistency));
+        }
+
+        #[test]
+        fn reports_private_key_without_primes() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(12)).unwrap();
+            let private_key = PrivateKey::new(user.private_key().n().clone(), user.private_key().d().clone());
+            let report = RSA::from_keys(user.public_key.clone(), private_key).validate();
+
+            assert_eq!(report.failures(), &[ValidationFailure::MissingPrimeFactors]);
+        }
     }
 
     mod chunk_message_tests {
@@ -85,7 +696,7 @@ mod tests {
                 vec![108, 100, 33, 0, 0],
             ];
 
-            assert_eq!(chunk_message(message, chunk_size), expected_chunks);
+            assert_eq!(chunk_message(message, chunk_size).unwrap(), expected_chunks);
         }
 
         #[test]
@@ -102,67 +713,523 @@ mod tests {
                 vec![33, 0],
             ];
 
-            assert_eq!(chunk_message(message, chunk_size), expected_chunks);
+            assert_eq!(chunk_message(message, chunk_size).unwrap(), expected_chunks);
+        }
+
+        #[test]
+        fn uses_utf8_bytes() {
+            let expected_chunks: Vec<Vec<u8>> = vec![vec![0xc3, 0xa6, 0xc3], vec![0xb8, 0xc3, 0xa5]];
+            assert_eq!(chunk_message("æøå", 3).unwrap(), expected_chunks);
+        }
+
+        #[test]
+        fn exact_multiple_needs_no_fill_chunk() {
+            assert_eq!(chunk_bytes(b"abcd", 2).unwrap(), vec![b"ab".to_vec(), b"cd".to_vec()]);
+            assert!(chunk_bytes(b"", 2).unwrap().is_empty());
+        }
+
+        #[test]
+        fn framing_round_trips_and_rejects_bad_lengths() {
+            let framed = frame_bytes(b"hej\0", 7).unwrap();
+            assert_eq!(framed, [b'h', b'e', b'j', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4]);
+            assert_eq!(unframe_bytes(&framed).unwrap(), b"hej\0");
+            assert_eq!(framed_len(4, 7), 14);
+            assert_eq!(frame_bytes(b"", 0).err(), Some(Error::InvalidChunkSize));
+
+            assert_eq!(unframe_bytes(&framed[4..]).err(), Some(Error::InvalidFraming));
+            assert_eq!(unframe_bytes(&[0, 0, 0]).err(), Some(Error::InvalidFraming));
+            let mut framed = framed;
+            framed[5] = 1;
+            assert_eq!(unframe_bytes(&framed).err(), Some(Error::InvalidFraming));
+        }
+    }
+
+    mod ciphertext_container_tests {
+        use super::*;
+
+        fn encrypted(seed: u64, message: &str) -> (RSA, Ciphertext) {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+            let ciphertext = user.public_key.encrypt_message(message).unwrap();
+            (user, ciphertext)
+        }
+
+        #[test]
+        fn encodes_header_and_fixed_width_blocks() {
+            let (user, ciphertext) = encrypted(20, "Hej med dig");
+            let bytes = ciphertext.to_bytes();
+
+            assert_eq!(&bytes[..4], b"SOPK");
+            assert_eq!(bytes[4], 1);
+            assert_eq!(bytes[5], PaddingScheme::Pkcs1v15.id());
+            assert_eq!(&bytes[6..38], &user.public_key.fingerprint());
+            assert_eq!(&bytes[38..42], &64u32.to_be_bytes());
+            assert_eq!(&bytes[106..114], &1u64.to_be_bytes());
+            assert_eq!(&bytes[114..122], &11u64.to_be_bytes());
+            assert_eq!(bytes.len(), 42 + 64 + 16);
+        }
+
+        #[test]
+        fn round_trips_through_bytes() {
+            let message = "Beskeden kan gemmes i en fil og sendes til en anden maskine.";
+            let (user, ciphertext) = encrypted(21, message);
+
+            let parsed = Ciphertext::from_bytes(&ciphertext.to_bytes()).unwrap();
+            assert_eq!(parsed, ciphertext);
+            assert_eq!(user.decrypt_message(&parsed).unwrap(), message);
+        }
+
+        #[test]
+        fn new_rejects_blocks_not_below_the_modulus() {
+            let (user, ciphertext) = encrypted(25, "Hej");
+            let n = user.public_key.n.clone();
+            let wrapped = Ciphertext::new(&user.public_key, &PaddingScheme::Pkcs1v15, 3, ciphertext.blocks().to_vec()).unwrap();
+            assert_eq!(wrapped, ciphertext);
+
+            for block in [n.clone(), n << 8u32] {
+                let result = Ciphertext::new(&user.public_key, &PaddingScheme::Pkcs1v15, 3, vec![block]);
+                assert_eq!(result.err(), Some(Error::InvalidCiphertext("block is not smaller than the modulus")));
+            }
+        }
+
+        #[test]
+        fn parser_rejects_truncated_and_malformed_input() {
+            let bytes = encrypted(22, "Hej").1.to_bytes();
+
+            for length in 0..bytes.len() {
+                assert!(Ciphertext::from_bytes(&bytes[..length]).is_err(), "accepted {} bytes", length);
+            }
+            let mut trailing = bytes.clone();
+            trailing.push(0);
+            assert_eq!(Ciphertext::from_bytes(&trailing).err(), Some(Error::InvalidCiphertext("blocks do not match the block count")));
+
+            for (index, expected) in [(0, "not a ciphertext container"), (4, "unsupported version"), (5, "unknown padding scheme")] {
+                let mut tampered = bytes.clone();
+                tampered[index] ^= 0x55;
+                assert_eq!(Ciphertext::from_bytes(&tampered).err(), Some(Error::InvalidCiphertext(expected)));
+            }
+        }
+
+        #[test]
+        fn decryption_rejects_tampered_containers() {
+            let (user, ciphertext) = encrypted(23, "Hej");
+            let bytes = ciphertext.to_bytes();
+            let tamper = |index: usize, value: u8| {
+                let mut tampered = bytes.clone();
+                tampered[index] = value;
+                user.decrypt_message(&Ciphertext::from_bytes(&tampered).unwrap()).err()
+            };
+
+            // Fingerprint, length and block contents
+            assert_eq!(tamper(6, bytes[6] ^ 1), Some(Error::WrongKey));
+            assert_eq!(tamper(121, 4), Some(Error::InvalidFraming));
+            assert_eq!(tamper(114, 1), Some(Error::InvalidCiphertext("block count does not match the length")));
+            assert_eq!(tamper(42, 0xff), Some(Error::InvalidCiphertext("block is not smaller than the modulus")));
+
+            let other = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(24)).unwrap();
+            assert_eq!(other.decrypt_message(&ciphertext).err(), Some(Error::WrongKey));
+        }
+    }
+
+    mod parallel_tests {
+        use super::*;
+
+        #[test]
+        fn parallel_encryption_matches_serial() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(40)).unwrap();
+
+            for padding in [&PaddingScheme::Textbook, &PaddingScheme::Pkcs1v15] {
+                for length in [0, 1, 55, 5_000] {
+                    let message: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+                    let serial = user.public_key
+                        .encrypt_bytes_with_rng(&message, padding, &mut ChaCha20Rng::seed_from_u64(length))
+                        .unwrap();
+                    let parallel = user.public_key
+                        .par_encrypt_bytes_with_rng(&message, padding, &mut ChaCha20Rng::seed_from_u64(length))
+                        .unwrap();
+
+                    assert_eq!(parallel, serial, "{:?}, length {}", padding, length);
+                    assert_eq!(user.par_decrypt_bytes(&parallel).unwrap(), message);
+                    assert_eq!(user.par_decrypt_bytes(&serial).unwrap(), user.decrypt_bytes(&serial).unwrap());
+                }
+            }
+        }
+
+        #[test]
+        fn parallel_message_round_trip() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(41)).unwrap();
+            let message = "Hver blok krypteres for sig, men rækkefølgen skal bevares. ".repeat(40);
+
+            let ciphertext = user.public_key.par_encrypt_message(&message).unwrap();
+            assert_eq!(ciphertext.blocks().len(), user.public_key.encrypt_message(&message).unwrap().blocks().len());
+            assert_eq!(user.par_decrypt_message(&ciphertext).unwrap(), message);
+        }
+
+        #[test]
+        fn parallel_decryption_reports_the_same_errors() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(42)).unwrap();
+            let other = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(43)).unwrap();
+            let bytes = user.public_key.encrypt_bytes(&[7u8; 500]).unwrap().to_bytes();
+
+            assert_eq!(other.par_decrypt_bytes(&Ciphertext::from_bytes(&bytes).unwrap()).err(), Some(Error::WrongKey));
+            for index in [42, 42 + 64 * 3, bytes.len() - 1] {
+                let mut tampered = bytes.clone();
+                tampered[index] = 0xff;
+                let tampered = Ciphertext::from_bytes(&tampered).unwrap();
+                assert_eq!(user.par_decrypt_bytes(&tampered).err(), user.decrypt_bytes(&tampered).err());
+                assert!(user.par_decrypt_bytes(&tampered).is_err());
+            }
+        }
+    }
+
+    mod pkcs1_padding_tests {
+        use super::*;
+
+        #[test]
+        fn pad_has_the_rfc_8017_layout() {
+            let message = b"Hej";
+            let padded_message = pkcs1_pad(message, 64, &mut ChaCha20Rng::seed_from_u64(50)).unwrap();
+
+            assert_eq!(padded_message.len(), 64);
+            assert_eq!(&padded_message[..2], &[0x00, 0x02]);
+            assert!(padded_message[2..60].iter().all(|&b| b != 0));
+            assert_eq!(padded_message[60], 0x00);
+            assert_eq!(&padded_message[61..], message);
+            assert_eq!(pkcs1_unpad(&padded_message), Ok(&message[..]));
+        }
+
+        #[test]
+        fn pad_rejects_messages_without_room_for_eight_padding_bytes() {
+            let mut rng = ChaCha20Rng::seed_from_u64(51);
+            assert!(pkcs1_pad(&[0x41; 53], 64, &mut rng).is_ok());
+            assert_eq!(pkcs1_pad(&[0x41; 54], 64, &mut rng).err(), Some(Error::MessageTooLong));
+        }
+
+        #[test]
+        fn unpad_rejects_every_malformed_encoding() {
+            let valid = pkcs1_pad(b"Hej", 32, &mut ChaCha20Rng::seed_from_u64(52)).unwrap();
+            let malformed = |index: usize, value: u8| {
+                let mut padded_message = valid.clone();
+                padded_message[index] = value;
+                pkcs1_unpad(&padded_message).err()
+            };
+
+            assert_eq!(malformed(0, 0x01), Some(Error::InvalidPadding));
+            assert_eq!(malformed(1, 0x01), Some(Error::InvalidPadding));
+            // Without the separator the whole block would be padding
+            assert_eq!(malformed(28, 0x01), Some(Error::InvalidPadding));
+            // A separator after seven padding bytes is too early, after eight it is fine
+            assert_eq!(malformed(9, 0x00), Some(Error::InvalidPadding));
+            let mut shortest = valid.clone();
+            shortest[10] = 0x00;
+            assert_eq!(pkcs1_unpad(&shortest).unwrap(), &valid[11..]);
+        }
+
+        #[test]
+        fn equal_messages_encrypt_differently() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(53)).unwrap();
+            let first = user.public_key.encrypt_message("Ja").unwrap();
+            let second = user.public_key.encrypt_message("Ja").unwrap();
+
+            assert_eq!(first.padding(), PaddingScheme::Pkcs1v15);
+            assert_ne!(first.blocks(), second.blocks());
+            assert_eq!(user.decrypt_message(&first).unwrap(), user.decrypt_message(&second).unwrap());
+
+            // Textbook chunks carry no randomness
+            let textbook = |seed| user.public_key
+                .encrypt_bytes_with_rng(b"Ja", &PaddingScheme::Textbook, &mut ChaCha20Rng::seed_from_u64(seed))
+                .unwrap();
+            assert_eq!(textbook(1), textbook(2));
+        }
+
+        #[test]
+        fn decrypts_openssl_ciphertext() {
+            // openssl pkeyutl -encrypt -pubin -inkey openssl_rsa_2048_pub.pem -pkeyopt rsa_padding_mode:pkcs1
+            let block = fs::read("tests/fixtures/openssl_rsa_2048_pkcs1.bin").unwrap();
+            assert_eq!(pkcs1_unpad(&decrypt_block(&openssl_key(), &block)), Ok(&b"Hej fra OpenSSL"[..]));
+        }
+
+        #[test]
+        fn openssl_decrypts_our_ciphertext() {
+            let rsa = openssl_key();
+            let ciphertext = rsa.public_key
+                .encrypt_bytes_with_rng(b"Hej fra SOP-KODE", &PaddingScheme::Pkcs1v15, &mut ChaCha20Rng::seed_from_u64(54))
+                .unwrap();
+            assert_eq!(ciphertext.to_bytes(), fs::read("tests/fixtures/sop_kode_pkcs1.sopk").unwrap());
+
+            // The block at bytes 42..298 of the fixture, decrypted with
+            // openssl pkeyutl -decrypt -inkey openssl_rsa_2048.pem -pkeyopt rsa_padding_mode:pkcs1
+            let recovered = fs::read("tests/fixtures/sop_kode_pkcs1_decrypted.bin").unwrap();
+            let block = &ciphertext.to_bytes()[42..298];
+            assert_eq!(pkcs1_unpad(&decrypt_block(&rsa, block)).unwrap(), recovered);
+            assert_eq!(recovered, frame_bytes(b"Hej fra SOP-KODE", 256 - PKCS1_OVERHEAD).unwrap());
+        }
+    }
+
+    mod oaep_padding_tests {
+        use super::*;
+        use crate::common::{hex, FixedRng};
+        use sop_kode::hash::{sha256, HashAlg};
+
+        #[test]
+        fn matches_rfc_8017_known_answers() {
+            let public_key = PublicKey { n: BigUint::from_bytes_be(&hex(OAEP_EXAMPLE_1_N)), e: BigUint::from(65537u32) };
+            let p = BigUint::from_bytes_be(&hex(OAEP_EXAMPLE_1_P));
+            let q = BigUint::from_bytes_be(&hex(OAEP_EXAMPLE_1_Q));
+            let rsa = RSA::from_primes(p, q, public_key.e.clone()).unwrap();
+            assert_eq!(rsa.public_key.n, public_key.n);
+
+            for (message, seed, expected) in OAEP_EXAMPLE_1_ENCRYPTIONS {
+                let (message, expected) = (hex(message), hex(expected));
+                let padded_message = oaep_pad(&message, 128, b"", HashAlg::Sha1, &mut FixedRng(hex(seed))).unwrap();
+                let encrypted = public_key.encrypt(&BigUint::from_bytes_be(&padded_message), &public_key).unwrap();
+                assert_eq!(encrypted.to_bytes_be(), expected);
+
+                let decrypted = decrypt_block(&rsa, &expected);
+                assert_eq!(oaep_unpad(&decrypted, b"", HashAlg::Sha1).unwrap(), message);
+                assert_eq!(oaep_unpad(&decrypted, b"", HashAlg::Sha256).err(), Some(Error::InvalidPadding));
+            }
+        }
+
+        #[test]
+        fn matches_ciphertexts_checked_by_openssl() {
+            // Encoded with a fixed seed, and decrypted with
+            // openssl pkeyutl -decrypt -pkeyopt rsa_padding_mode:oaep -pkeyopt rsa_oaep_md:sha256
+            //   -pkeyopt rsa_mgf1_md:sha256 [-pkeyopt rsa_oaep_label:534f502d4b4f4445]
+            let vectors = [
+                (&b"Hej OAEP"[..], &b""[..], (0..32).collect::<Vec<u8>>(), "openssl_rsa_2048_oaep.bin"),
+                (b"Beskeden er bundet til sin etiket.", b"SOP-KODE", sha256(b"seed").to_vec(), "openssl_rsa_2048_oaep_label.bin"),
+            ];
+            let rsa = openssl_key();
+
+            for (message, label, seed, name) in vectors {
+                let expected = fs::read(format!("tests/fixtures/{}", name)).unwrap();
+                let padded_message = oaep_pad(message, 256, label, HashAlg::Sha256, &mut FixedRng(seed)).unwrap();
+                let encrypted = rsa.public_key.encrypt(&BigUint::from_bytes_be(&padded_message), &rsa.public_key).unwrap();
+                assert_eq!(encrypted.to_bytes_be(), expected, "{}", name);
+
+                let decrypted = decrypt_block(&rsa, &expected);
+                assert_eq!(decrypted, padded_message);
+                assert_eq!(oaep_unpad(&decrypted, label, HashAlg::Sha256).unwrap(), message);
+                assert_eq!(oaep_unpad(&decrypted, b"another label", HashAlg::Sha256).err(), Some(Error::InvalidPadding));
+            }
+        }
+
+        #[test]
+        fn mgf1_extends_the_digest_with_a_counter() {
+            let mask = mgf1(HashAlg::Sha256, b"seed", 80);
+            assert_eq!(mask.len(), 80);
+            assert_eq!(&mask[..32], &sha256(b"seed\0\0\0\0"));
+            assert_eq!(&mask[32..64], &sha256(b"seed\0\0\0\x01"));
+            assert_eq!(&mask[64..], &sha256(b"seed\0\0\0\x02")[..16]);
+            assert_eq!(mgf1(HashAlg::Sha256, b"seed", 40), &mask[..40]);
+        }
+
+        #[test]
+        fn pad_rejects_messages_longer_than_k_minus_66() {
+            let mut rng = ChaCha20Rng::seed_from_u64(60);
+            assert_eq!(oaep_pad(&[0x41; 62], 128, b"", HashAlg::Sha256, &mut rng).unwrap().len(), 128);
+            assert_eq!(oaep_pad(&[0x41; 63], 128, b"", HashAlg::Sha256, &mut rng).err(), Some(Error::MessageTooLong));
+        }
+
+        #[test]
+        fn unpad_rejects_any_changed_byte() {
+            let padded_message = oaep_pad(b"Hej", 128, b"", HashAlg::Sha256, &mut ChaCha20Rng::seed_from_u64(61)).unwrap();
+            assert_eq!(oaep_unpad(&padded_message, b"", HashAlg::Sha256).unwrap(), b"Hej");
+
+            for index in [0, 1, 32, 33, 64, 100, 124, 127] {
+                let mut tampered = padded_message.clone();
+                tampered[index] ^= 0x01;
+                assert_eq!(oaep_unpad(&tampered, b"", HashAlg::Sha256).err(), Some(Error::InvalidPadding), "index {}", index);
+            }
+            assert_eq!(oaep_unpad(&padded_message[..oaep_overhead(HashAlg::Sha256) - 1], b"", HashAlg::Sha256).err(), Some(Error::InvalidPadding));
+        }
+
+        #[test]
+        fn messages_round_trip_with_oaep() {
+            let user = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(62)).unwrap();
+            let message = "OAEP er standarden for alt andet end undervisning. ".repeat(5);
+
+            let ciphertext = user.public_key
+                .encrypt_bytes_with_rng(message.as_bytes(), &PaddingScheme::oaep(HashAlg::Sha256), &mut ChaCha20Rng::seed_from_u64(63))
+                .unwrap();
+            assert_eq!(ciphertext.padding(), PaddingScheme::oaep(HashAlg::Sha256));
+            assert_eq!(ciphertext.to_bytes()[5], 2);
+            assert_eq!(user.public_key.chunk_size(&PaddingScheme::oaep(HashAlg::Sha256)).unwrap(), 128 - oaep_overhead(HashAlg::Sha256));
+            assert_eq!(user.decrypt_message(&Ciphertext::from_bytes(&ciphertext.to_bytes()).unwrap()).unwrap(), message);
+
+            let small = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(64)).unwrap();
+            let result = small.public_key.encrypt_bytes_with_rng(b"Hej", &PaddingScheme::oaep(HashAlg::Sha256), &mut ChaCha20Rng::seed_from_u64(65));
+            assert_eq!(result.err(), Some(Error::KeyTooSmall));
+        }
+
+        #[test]
+        fn messages_are_bound_to_their_label_and_hash() {
+            let user = openssl_key();
+            let padding = PaddingScheme::oaep(HashAlg::Sha512).with_label(b"SOP-KODE");
+
+            let ciphertext = user.public_key
+                .encrypt_bytes_with_rng(b"Hej med etiket", &padding, &mut ChaCha20Rng::seed_from_u64(67))
+                .unwrap();
+            assert_eq!(ciphertext.to_bytes()[5], 7);
+            assert!(ciphertext.is_labelled());
+            assert_eq!(ciphertext.padding(), PaddingScheme::oaep(HashAlg::Sha512));
+            assert_eq!(user.public_key.chunk_size(&padding).unwrap(), 256 - 2 * 64 - 2);
+
+            let decoded = Ciphertext::from_bytes(&ciphertext.to_bytes()).unwrap();
+            assert_eq!(user.decrypt_bytes_with_label(&decoded, b"SOP-KODE").unwrap(), b"Hej med etiket");
+            assert_eq!(user.par_decrypt_bytes_with_label(&decoded, b"SOP-KODE").unwrap(), b"Hej med etiket");
+            assert_eq!(user.decrypt_bytes(&decoded).err(), Some(Error::LabelMismatch));
+            assert_eq!(user.decrypt_bytes_with_label(&decoded, b"SOP-KODF").err(), Some(Error::InvalidPadding));
+
+            // Without a label the id records only the hash, and a label given anyway is refused
+            let unlabelled = user.public_key
+                .encrypt_bytes_with_rng(b"Hej", &PaddingScheme::oaep(HashAlg::Sha1), &mut ChaCha20Rng::seed_from_u64(68))
+                .unwrap();
+            assert_eq!(unlabelled.to_bytes()[5], 3);
+            assert!(!unlabelled.is_labelled());
+            assert_eq!(user.decrypt_bytes(&unlabelled).unwrap(), b"Hej");
+            assert_eq!(user.decrypt_bytes_with_label(&unlabelled, b"SOP-KODE").err(), Some(Error::LabelMismatch));
+
+            // A label means nothing to the other schemes
+            assert_eq!(PaddingScheme::Pkcs1v15.with_label(b"SOP-KODE"), PaddingScheme::Pkcs1v15);
+            let pkcs1 = user.public_key.encrypt_bytes(b"Hej").unwrap();
+            assert_eq!(user.decrypt_bytes_with_label(&pkcs1, b"SOP-KODE").err(), Some(Error::LabelMismatch));
+        }
+
+        #[test]
+        fn scheme_ids_round_trip() {
+            for id in 0..=7 {
+                let scheme = PaddingScheme::from_id(id).unwrap();
+                let scheme = if PaddingScheme::is_labelled_id(id) { scheme.with_label(b"etiket") } else { scheme };
+                assert_eq!(scheme.id(), id);
+            }
+            assert_eq!(PaddingScheme::from_id(8), None);
         }
     }
 
     mod message_encryption_tests {
         use super::*;
 
+        #[test]
+        fn encrypt_between_keys_of_different_sizes() {
+            let mut rng = ChaCha20Rng::seed_from_u64(12);
+            let small = RSA::new_with_rng(512, &mut rng).unwrap();
+            let large = RSA::new_with_rng(1024, &mut rng).unwrap();
+            let message = "A message long enough to need more than one chunk for either of the two keys involved.";
+
+            // Chunks are sized for the recipient, whichever key pair the sender holds
+            let to_small = large.encrypt_message(message, small.public_key.clone()).unwrap();
+            assert_eq!(small.decrypt_message(&to_small).unwrap(), message);
+
+            let to_large = small.encrypt_message(message, large.public_key.clone()).unwrap();
+            assert_eq!(large.decrypt_message(&to_large).unwrap(), message);
+
+            // Only the recipient's public key is needed
+            let encrypted_message = small.public_key.encrypt_message(message).unwrap();
+            assert!(encrypted_message.blocks().iter().all(|chunk| chunk < &small.public_key.n));
+            assert_eq!(small.decrypt_message(&encrypted_message).unwrap(), message);
+        }
+
+        #[test]
+        fn encrypt_bytes_uses_recipient_chunk_size() {
+            let recipient = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(13)).unwrap();
+            let bytes = [0xffu8; 100];
+
+            let encrypted_chunks = recipient.public_key.encrypt_bytes(&bytes).unwrap();
+            assert_eq!(recipient.public_key.chunk_size(&PaddingScheme::Pkcs1v15).unwrap(), 64 - PKCS1_OVERHEAD);
+            // The 100 bytes and the 8-byte length suffix fill three 53-byte chunks
+            assert_eq!(encrypted_chunks.blocks().len(), 3);
+            assert_eq!(recipient.decrypt_bytes(&encrypted_chunks).unwrap(), bytes);
+
+            let textbook = recipient.public_key
+                .encrypt_bytes_with_rng(&bytes, &PaddingScheme::Textbook, &mut ChaCha20Rng::seed_from_u64(13))
+                .unwrap();
+            assert_eq!(recipient.public_key.chunk_size(&PaddingScheme::Textbook).unwrap(), 63);
+            assert_eq!(textbook.blocks().len(), 2);
+            assert_eq!(recipient.decrypt_bytes(&textbook).unwrap(), bytes);
+        }
+
+        #[test]
+        fn binary_payloads_round_trip_exactly() {
+            let recipient = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(14)).unwrap();
+            let payloads: [&[u8]; 5] = [b"", &[0], &[0, 0, 1, 0, 0], &[0xff; 63], &[0x80, 0x00, 0x7f, 0x00, 0x00, 0x00]];
+
+            for payload in payloads {
+                let encrypted_chunks = recipient.public_key.encrypt_bytes(payload).unwrap();
+                assert_eq!(recipient.decrypt_bytes(&encrypted_chunks).unwrap(), payload);
+            }
+        }
+
+        #[test]
+        fn non_ascii_text_round_trips() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(15)).unwrap();
+            let message = "Rødgrød med fløde på Ærø 🇩🇰\0";
+
+            let encrypted_message = user.public_key.encrypt_message(message).unwrap();
+            assert_eq!(user.decrypt_message(&encrypted_message).unwrap(), message);
+        }
+
+        #[test]
+        fn decrypt_message_rejects_invalid_utf8() {
+            let user = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(16)).unwrap();
+            let encrypted_chunks = user.public_key.encrypt_bytes(&[0xc3, 0x28]).unwrap();
+            assert_eq!(user.decrypt_message(&encrypted_chunks).err(), Some(Error::InvalidUtf8));
+        }
+
         #[test]
         fn encrypt_and_decrypt_short_message() {
-            let (public_key, private_key) = generate_keys(1024).unwrap();
+            let user = RSA::new(1024).unwrap();
             let message = "Hello";
 
-            let encrypted_message = encrypt_message(message, &public_key);
-            let decrypted_message = decrypt_message(encrypted_message, private_key);
+            let encrypted_message = user.encrypt_message(message, user.public_key.clone()).unwrap();
+            let decrypted_message = user.decrypt_message(&encrypted_message).unwrap();
 
             assert_eq!(message, decrypted_message);
         }
 
         #[test]
         fn encrypt_and_decrypt_long_message() {
-            let (public_key, private_key) = generate_keys(1024).unwrap();
+            let user = RSA::new(1024).unwrap();
             let message = "This is a very long message that exceeds the chunk size.";
 
-            let encrypted_message = encrypt_message(message, &public_key);
-            let decrypted_message = decrypt_message(encrypted_message, private_key);
+            let encrypted_message = user.encrypt_message(message, user.public_key.clone()).unwrap();
+            let decrypted_message = user.decrypt_message(&encrypted_message).unwrap();
 
             assert_eq!(message, decrypted_message);
         }
 
         #[test]
         fn encrypt_and_decrypt_with_small_key() {
-            let (public_key, private_key) = generate_keys(512).unwrap();
+            let user = RSA::new(512).unwrap();
             let message = "This is a test message.";
 
-            let encrypted_message = encrypt_message(message, &public_key);
-            let decrypted_message = decrypt_message(encrypted_message, private_key);
+            let encrypted_message = user.encrypt_message(message, user.public_key.clone()).unwrap();
+            let decrypted_message = user.decrypt_message(&encrypted_message).unwrap();
 
             assert_eq!(message, decrypted_message);
         }
 
         #[test]
         fn encrypt_and_decrypt_with_large_key() {
-            let (public_key, private_key) = generate_keys(2048).unwrap();
+            let user = RSA::new(2048).unwrap();
             let message = "This is a test message.";
 
-            let encrypted_message = encrypt_message(message, &public_key);
-            let decrypted_message = decrypt_message(encrypted_message, private_key);
+            let encrypted_message = user.encrypt_message(message, user.public_key.clone()).unwrap();
+            let decrypted_message = user.decrypt_message(&encrypted_message).unwrap();
 
             assert_eq!(message, decrypted_message);
         }
 
         #[test]
         fn encrypt_and_decrypt_very_long_message() {
-            let (public_key, private_key) = generate_keys(1024).unwrap();
+            let user = RSA::new(1024).unwrap();
             let message = "This is a very long message. It is so long that it exceeds the chunk size many times over. \
                    In fact, it is so long that it might even be considered a small book or a short novel. \
                    It contains many characters, words, sentences, and paragraphs, and it goes on and on and on. \
                    But despite its length, it is still just a single message, and it should be encrypted and decrypted correctly.";
 
-            let encrypted_message = encrypt_message(message, &public_key);
-            let decrypted_message = decrypt_message(encrypted_message, private_key);
+            let encrypted_message = user.encrypt_message(message, user.public_key.clone()).unwrap();
+            let decrypted_message = user.decrypt_message(&encrypted_message).unwrap();
 
             assert_eq!(message, decrypted_message);
         }
@@ -173,47 +1240,48 @@ mod tests {
     fn returns_correct_estimation_for_small_n() {
         let n = BigUint::from_u64(1024).unwrap();
         let estimated_time = estimate_brute_force_time(&n);
-        assert!(estimated_time > 0.0);
+        assert_eq!(format_duration(estimated_time), "0.00 seconds");
     }
 
     #[test]
     fn returns_correct_estimation_for_large_n() {
         let n = BigUint::from_u64(1 << 20).unwrap(); // 2^20
         let estimated_time = estimate_brute_force_time(&n);
-        assert!(estimated_time > 0.0);
+        assert_eq!(format_duration(estimated_time), "2.10 seconds");
     }
 
     mod time_format_tests {
         use super::*;
 
+        /// Builds a modulus with the given bit length, so the estimate is `2^bits / 10^6` seconds.
+        fn estimate_for_bits(bits: usize) -> String {
+            let n = BigUint::one() << (bits - 1);
+            format_duration(estimate_brute_force_time(&n))
+        }
+
         #[test]
         fn format_duration_returns_correct_value_for_seconds() {
-            let seconds = 45.0;
-            assert_eq!(format_duration(seconds), "45.00 seconds");
+            assert_eq!(estimate_for_bits(25), "33.55 seconds");
         }
 
         #[test]
         fn format_duration_returns_correct_value_for_minutes() {
-            let seconds = 120.0;
-            assert_eq!(format_duration(seconds), "2.00 minutes");
+            assert_eq!(estimate_for_bits(27), "2.24 minutes");
         }
 
         #[test]
         fn format_duration_returns_correct_value_for_hours() {
-            let seconds = 7200.0;
-            assert_eq!(format_duration(seconds), "2.00 hours");
+            assert_eq!(estimate_for_bits(32), "1.19 hours");
         }
 
         #[test]
         fn format_duration_returns_correct_value_for_days() {
-            let seconds = 172800.0;
-            assert_eq!(format_duration(seconds), "2.00 days");
+            assert_eq!(estimate_for_bits(37), "1.59 days");
         }
 
         #[test]
         fn format_duration_returns_correct_value_for_years() {
-            let seconds = 63115200.0;
-            assert_eq!(format_duration(seconds), "2.00 years");
+            assert_eq!(estimate_for_bits(45), "1.11 years");
         }
     }
 }
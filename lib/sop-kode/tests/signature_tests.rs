@@ -0,0 +1,342 @@
+mod common;
+
+use std::fs;
+
+use num_bigint::BigUint;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use sop_kode::hash::HashAlg;
+use sop_kode::rsa::encryption::{PrivateKey, PublicKey};
+use sop_kode::rsa::signature::emsa_pkcs1_v15_encode;
+use sop_kode::rsa::*;
+use sop_kode::Error;
+
+use common::{hex, FixedRng};
+
+/// Reads a checked-in binary fixture from `tests/fixtures`.
+fn fixture(name: &str) -> Vec<u8> {
+    fs::read(format!("tests/fixtures/{}", name)).expect("Failed to read fixture")
+}
+
+/// Loads a private key fixture.
+fn private_key(name: &str) -> RSA {
+    match load_key(format!("tests/fixtures/{}", name)).unwrap() {
+        Key::Private(rsa) => rsa,
+        Key::Public(_) => panic!("expected a private key"),
+    }
+}
+
+/// The 1024-bit key of Example 1 in pss-vect.txt, the RSASSA-PSS test vectors published with RFC 8017.
+const PSS_EXAMPLE_1_N: &str = "a56e4a0e701017589a5187dc7ea841d156f2ec0e36ad52a44dfeb1e61f7ad991\
+                                 d8c51056ffedb162b4c0f283a12a88a394dff526ab7291cbb307ceabfce0b1df\
+                                 d5cd9508096d5b2b8b6df5d671ef6377c0921cb23c270a70e2598e6ff89d19f1\
+                                 05acc2d3f0cb35f29280e1386b6f64c4ef22e1e1f20d0ce8cffb2249bd9a2137";
+const PSS_EXAMPLE_1_D: &str = "33a5042a90b27d4f5451ca9bbbd0b44771a101af884340aef9885f2a4bbe92e8\
+                                 94a724ac3c568c8f97853ad07c0266c8c6a3ca0929f1e8f11231884429fc4d9a\
+                                 e55fee896a10ce707c3ed7e734e44727a39574501a532683109c2abacaba283c\
+                                 31b4bd2f53c3ee37e352cee34f9e503bd80c0622ad79c6dcee883547c6a3b325";
+const PSS_EXAMPLE_1_P: &str = "e7e8942720a877517273a356053ea2a1bc0c94aa72d55c6e86296b2dfc967948\
+                                 c0a72cbccca7eacb35706e09a1df55a1535bd9b3cc34160b3b6dcd3eda8e6443";
+const PSS_EXAMPLE_1_Q: &str = "b69dca1cf7d4d7ec81e75b90fcca874abcde123fd2700180aa90479b6e48de8d\
+                                 67ed24f9f19d85ba275874f542cd20dc723e6963364a1f9425452b269a6799fd";
+/// The message, salt and signature of Examples 1.1 and 1.2 in pss-vect.txt, signed with SHA-1 and MGF1-SHA-1.
+const PSS_EXAMPLE_1_SIGNATURES: [(&str, &str, &str); 2] = [
+    (
+        "cdc87da223d786df3b45e0bbbc721326d1ee2af806cc315475cc6f0d9c66e1b6\
+         2371d45ce2392e1ac92844c310102f156a0d8d52c1f4c40ba3aa65095786cb76\
+         9757a6563ba958fed0bcc984e8b517a3d5f515b23b8a41e74aa867693f90dfb0\
+         61a6e86dfaaee64472c00e5f20945729cbebe77f06ce78e08f4098fba41f9d61\
+         93c0317e8b60d4b6084acb42d29e3808a3bc372d85e331170fcbf7cc72d0b71c\
+         296648b3a4d10f416295d0807aa625cab2744fd9ea8fd223c42537029828bd16\
+         be02546f130fd2e33b936d2676e08aed1b73318b750a0167d0",
+        "dee959c7e06411361420ff80185ed57f3e6776af",
+        "9074308fb598e9701b2294388e52f971faac2b60a5145af185df5287b5ed2887\
+         e57ce7fd44dc8634e407c8e0e4360bc226f3ec227f9d9e54638e8d31f5051215\
+         df6ebb9c2f9579aa77598a38f914b5b9c1bd83c4e2f9f382a0d0aa3542ffee65\
+         984a601bc69eb28deb27dca12c82c2d4c3f66cd500f1ff2b994d8a4e30cbb33c",
+    ),
+    (
+        "851384cdfe819c22ed6c4ccb30daeb5cf059bc8e1166b7e3530c4c233e2b5f8f\
+         71a1cca582d43ecc72b1bca16dfc7013226b9e",
+        "ef2869fa40c346cb183dab3d7bffc98fd56df42d",
+        "3ef7f46e831bf92b32274142a585ffcefbdca7b32ae90d10fb0f0c729984f04e\
+         f29a9df0780775ce43739b97838390db0a5505e63de927028d9d29b219ca2c45\
+         17832558a55d694a6d25b9dab66003c4cccd907802193be5170d26147d37b935\
+         90241be51c25055f47ef62752cfbe21418fafe98c22c4d4d47724fdb5669e843",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod pkcs1_signature_tests {
+        use super::*;
+
+        #[test]
+        fn signatures_match_openssl_dgst() {
+            // openssl dgst -<hash> -sign openssl_rsa_2048.pem signed_message.txt
+            let rsa = private_key("openssl_rsa_2048.pem");
+            let message = fixture("signed_message.txt");
+
+            for (hash, name) in [
+                (HashAlg::Sha1, "openssl_rsa_2048_sha1.sig"),
+                (HashAlg::Sha256, "openssl_rsa_2048_sha256.sig"),
+                (HashAlg::Sha512, "openssl_rsa_2048_sha512.sig"),
+            ] {
+                let expected = fixture(name);
+                assert_eq!(rsa.sign(&message, hash).unwrap(), expected, "{:?}", hash);
+                assert_eq!(rsa.public_key.verify(&message, &expected, hash), Ok(()));
+            }
+        }
+
+        #[test]
+        fn signatures_with_a_sop_kode_key_match_openssl() {
+            // openssl dgst -sha256 -sign sop_kode_rsa_1024.pem signed_message.txt
+            let rsa = private_key("sop_kode_rsa_1024.pem");
+            let signature = rsa.sign(&fixture("signed_message.txt"), HashAlg::Sha256).unwrap();
+            assert_eq!(signature, fixture("sop_kode_rsa_1024_sha256.sig"));
+        }
+
+        #[test]
+        fn encoding_has_the_rfc_8017_layout() {
+            let encoded = emsa_pkcs1_v15_encode(b"abc", HashAlg::Sha256, 64).unwrap();
+
+            assert_eq!(encoded.len(), 64);
+            assert_eq!(&encoded[..2], &[0x00, 0x01]);
+            assert!(encoded[2..12].iter().all(|&b| b == 0xff));
+            assert_eq!(encoded[12], 0x00);
+            assert_eq!(&encoded[13..15], &[0x30, 0x31]);
+            assert_eq!(&encoded[32..], &sop_kode::hash::sha256(b"abc"));
+        }
+
+        #[test]
+        fn verify_rejects_anything_changed() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(70)).unwrap();
+            let other = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(71)).unwrap();
+            let message = b"Overfor 100 kroner til konto 1234.";
+            let signature = rsa.sign(message, HashAlg::Sha256).unwrap();
+            assert_eq!(rsa.public_key.verify(message, &signature, HashAlg::Sha256), Ok(()));
+
+            let mut tampered = signature.clone();
+            tampered[64] ^= 0x01;
+            assert_eq!(rsa.public_key.verify(message, &tampered, HashAlg::Sha256), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.verify(b"Overfor 900 kroner til konto 1234.", &signature, HashAlg::Sha256), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.verify(message, &signature, HashAlg::Sha512), Err(Error::InvalidSignature));
+            assert_eq!(other.public_key.verify(message, &signature, HashAlg::Sha256), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.verify(message, &signature[1..], HashAlg::Sha256), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.verify(message, &[0xff; 128], HashAlg::Sha256), Err(Error::InvalidSignature));
+        }
+
+        #[test]
+        fn sign_rejects_keys_too_small_for_the_digest() {
+            let rsa = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(72)).unwrap();
+            let signature = rsa.sign(b"Hej", HashAlg::Sha256).unwrap();
+            assert_eq!(rsa.public_key.verify(b"Hej", &signature, HashAlg::Sha256), Ok(()));
+            assert_eq!(rsa.sign(b"Hej", HashAlg::Sha512).err(), Some(Error::KeyTooSmall));
+        }
+
+        #[test]
+        fn signatures_round_trip_through_armor() {
+            let rsa = private_key("sop_kode_rsa_1024.pem");
+            let message = b"Underskrevet og pakket ind.";
+            let signature = rsa.sign(message, HashAlg::Sha256).unwrap();
+
+            let armored = signature_to_armor(&signature);
+            assert!(armored.starts_with("-----BEGIN SOP-KODE SIGNATURE-----\n"));
+            let loaded = signature_from_armor(&armored).unwrap();
+            assert_eq!(loaded, signature);
+            assert_eq!(rsa.public_key.verify(message, &loaded, HashAlg::Sha256), Ok(()));
+
+            // A message block is not a signature
+            let ciphertext = rsa.public_key.encrypt_bytes(message).unwrap();
+            assert_eq!(signature_from_armor(&ciphertext.to_armor()).err(), Some(Error::InvalidArmor("unexpected armor type")));
+        }
+    }
+
+    mod pss_signature_tests {
+        use super::*;
+
+        #[test]
+        fn matches_rfc_8017_known_answers() {
+            let n = BigUint::from_bytes_be(&hex(PSS_EXAMPLE_1_N));
+            let e = BigUint::from(65537u32);
+            let p = BigUint::from_bytes_be(&hex(PSS_EXAMPLE_1_P));
+            let q = BigUint::from_bytes_be(&hex(PSS_EXAMPLE_1_Q));
+            let d = BigUint::from_bytes_be(&hex(PSS_EXAMPLE_1_D));
+            let private_key = PrivateKey::from_primes(p, q, d).unwrap().with_blinding(e.clone()).unwrap();
+            let rsa = RSA::from_keys(PublicKey { n, e }, private_key);
+            assert!(rsa.validate().is_valid());
+
+            for (message, salt, expected) in PSS_EXAMPLE_1_SIGNATURES {
+                let (message, expected) = (hex(message), hex(expected));
+                let signature = rsa.sign_pss_with_rng(&message, HashAlg::Sha1, 20, &mut FixedRng(hex(salt))).unwrap();
+                assert_eq!(signature, expected);
+                assert_eq!(rsa.public_key.verify_pss(&message, &expected, HashAlg::Sha1), Ok(20));
+            }
+        }
+
+        #[test]
+        fn matches_signatures_checked_by_openssl() {
+            // Signed with a fixed salt, and checked with
+            // openssl dgst -<hash> -verify -sigopt rsa_padding_mode:pss -sigopt rsa_pss_saltlen:auto
+            let message = fixture("signed_message.txt");
+            let vectors = [
+                ("openssl_rsa_2048.pem", HashAlg::Sha256, sop_kode::hash::sha256(b"salt").to_vec(), "pss_rsa_2048_sha256.sig"),
+                ("openssl_rsa_2048.pem", HashAlg::Sha1, (0..20).collect(), "pss_rsa_2048_sha1.sig"),
+                ("openssl_rsa_2048.pem", HashAlg::Sha512, Vec::new(), "pss_rsa_2048_sha512.sig"),
+                // 1025 bits, so the encoding is a byte shorter than the modulus
+                ("openssl_rsa_1025.pem", HashAlg::Sha256, (100..132).collect(), "pss_rsa_1025_sha256.sig"),
+            ];
+
+            for (key, hash, salt, name) in vectors {
+                let rsa = private_key(key);
+                let salt_len = salt.len();
+                let signature = rsa.sign_pss_with_rng(&message, hash, salt_len, &mut FixedRng(salt)).unwrap();
+                assert_eq!(signature, fixture(name), "{}", name);
+                assert_eq!(rsa.public_key.verify_pss(&message, &signature, hash), Ok(salt_len));
+            }
+        }
+
+        #[test]
+        fn recovers_the_salt_length_of_openssl_signatures() {
+            // openssl dgst -sha256 -sign openssl_rsa_2048.pem -sigopt rsa_padding_mode:pss -sigopt rsa_pss_saltlen:<max|digest>
+            let public_key = private_key("openssl_rsa_2048.pem").public_key;
+            let message = fixture("signed_message.txt");
+
+            assert_eq!(public_key.verify_pss(&message, &fixture("openssl_rsa_2048_pss_max.sig"), HashAlg::Sha256), Ok(256 - 32 - 2));
+            assert_eq!(public_key.verify_pss(&message, &fixture("openssl_rsa_2048_pss_digest.sig"), HashAlg::Sha256), Ok(32));
+        }
+
+        #[test]
+        fn salts_make_signatures_differ() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(73)).unwrap();
+            let first = rsa.sign_pss(b"Hej", HashAlg::Sha256, 32).unwrap();
+            let second = rsa.sign_pss(b"Hej", HashAlg::Sha256, 32).unwrap();
+
+            assert_ne!(first, second);
+            assert_eq!(rsa.public_key.verify_pss(b"Hej", &first, HashAlg::Sha256), Ok(32));
+            assert_eq!(rsa.public_key.verify_pss(b"Hej", &second, HashAlg::Sha256), Ok(32));
+
+            // Without a salt there is nothing random left
+            assert_eq!(rsa.sign_pss(b"Hej", HashAlg::Sha256, 0).unwrap(), rsa.sign_pss(b"Hej", HashAlg::Sha256, 0).unwrap());
+        }
+
+        #[test]
+        fn verify_rejects_anything_changed() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(74)).unwrap();
+            let other = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(75)).unwrap();
+            let message = b"Overfor 100 kroner til konto 1234.";
+            let signature = rsa.sign_pss(message, HashAlg::Sha256, 32).unwrap();
+
+            for index in [0, 64, 127] {
+                let mut tampered = signature.clone();
+                tampered[index] ^= 0x01;
+                assert_eq!(rsa.public_key.verify_pss(message, &tampered, HashAlg::Sha256), Err(Error::InvalidSignature));
+            }
+            assert_eq!(rsa.public_key.verify_pss(b"Overfor 900 kroner til konto 1234.", &signature, HashAlg::Sha256), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.verify_pss(message, &signature, HashAlg::Sha1), Err(Error::InvalidSignature));
+            assert_eq!(other.public_key.verify_pss(message, &signature, HashAlg::Sha256), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.verify_pss(message, &signature[1..], HashAlg::Sha256), Err(Error::InvalidSignature));
+            // A PKCS#1 v1.5 signature is not a PSS signature
+            let pkcs1 = rsa.sign(message, HashAlg::Sha256).unwrap();
+            assert_eq!(rsa.public_key.verify_pss(message, &pkcs1, HashAlg::Sha256), Err(Error::InvalidSignature));
+        }
+
+        #[test]
+        fn sign_rejects_salts_that_do_not_fit() {
+            let rsa = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(76)).unwrap();
+            let signature = rsa.sign_pss(b"Hej", HashAlg::Sha256, 30).unwrap();
+            assert_eq!(rsa.public_key.verify_pss(b"Hej", &signature, HashAlg::Sha256), Ok(30));
+            assert_eq!(rsa.sign_pss(b"Hej", HashAlg::Sha256, 31).err(), Some(Error::KeyTooSmall));
+        }
+
+        #[test]
+        fn sign_and_verify_reject_a_zero_modulus() {
+            let rsa = RSA::from_keys(PublicKey { n: 0u64.into(), e: 3u64.into() }, PrivateKey::new(0u64.into(), 3u64.into()));
+            assert_eq!(rsa.sign_pss(b"Hej", HashAlg::Sha256, 0).err(), Some(Error::KeyTooSmall));
+            assert_eq!(rsa.public_key.verify_pss(b"Hej", &[], HashAlg::Sha256), Err(Error::KeyTooSmall));
+        }
+    }
+
+    mod blind_signature_tests {
+        use super::*;
+
+        #[test]
+        fn unblinded_signatures_verify_normally() {
+            let rsa = private_key("sop_kode_rsa_1024.pem");
+            let message = b"Stem paa kandidat 7.";
+
+            let (blinded, factor) = rsa.public_key.blind(message, HashAlg::Sha256).unwrap();
+            let blind_signature = rsa.sign_blinded(&blinded).unwrap();
+            let signature = rsa.public_key.unblind(&blind_signature, &factor).unwrap();
+
+            assert_eq!(rsa.public_key.verify(message, &signature, HashAlg::Sha256), Ok(()));
+            // RSASSA-PKCS1-v1_5 is deterministic, so it is the very signature the signer would have made
+            assert_eq!(signature, rsa.sign(message, HashAlg::Sha256).unwrap());
+        }
+
+        #[test]
+        fn signer_never_sees_the_message() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(80)).unwrap();
+            let message = b"Stem paa kandidat 7.";
+            let encoded = emsa_pkcs1_v15_encode(message, HashAlg::Sha256, 128).unwrap();
+            let digest = sop_kode::hash::sha256(message);
+
+            let (blinded, _) = rsa.public_key.blind_with_rng(message, HashAlg::Sha256, &mut ChaCha20Rng::seed_from_u64(81)).unwrap();
+            assert_eq!(blinded.len(), 128);
+            assert_ne!(blinded, encoded);
+            assert!(!blinded.windows(digest.len()).any(|window| window == digest));
+            assert!(!blinded.windows(message.len()).any(|window| window == message));
+            // Nor does the blinded message pass as a signature on it
+            assert_eq!(rsa.public_key.verify(message, &rsa.sign_blinded(&blinded).unwrap(), HashAlg::Sha256), Err(Error::InvalidSignature));
+        }
+
+        #[test]
+        fn blindings_differ_but_unblind_to_the_same_signature() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(82)).unwrap();
+            let message = b"Stem paa kandidat 7.";
+
+            let (first, first_factor) = rsa.public_key.blind(message, HashAlg::Sha256).unwrap();
+            let (second, second_factor) = rsa.public_key.blind(message, HashAlg::Sha256).unwrap();
+            assert_ne!(first, second);
+
+            let first = rsa.public_key.unblind(&rsa.sign_blinded(&first).unwrap(), &first_factor).unwrap();
+            let second = rsa.public_key.unblind(&rsa.sign_blinded(&second).unwrap(), &second_factor).unwrap();
+            assert_eq!(first, second);
+        }
+
+        #[test]
+        fn the_wrong_factor_gives_an_invalid_signature() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(83)).unwrap();
+            let message = b"Stem paa kandidat 7.";
+
+            let (blinded, _) = rsa.public_key.blind(message, HashAlg::Sha256).unwrap();
+            let (_, other_factor) = rsa.public_key.blind(message, HashAlg::Sha256).unwrap();
+            let signature = rsa.public_key.unblind(&rsa.sign_blinded(&blinded).unwrap(), &other_factor).unwrap();
+            assert_eq!(rsa.public_key.verify(message, &signature, HashAlg::Sha256), Err(Error::InvalidSignature));
+        }
+
+        #[test]
+        fn rejects_values_outside_the_modulus() {
+            let rsa = RSA::new_with_rng(1024, &mut ChaCha20Rng::seed_from_u64(84)).unwrap();
+            let (_, factor) = rsa.public_key.blind(b"Hej", HashAlg::Sha256).unwrap();
+
+            assert_eq!(rsa.sign_blinded(&[0xff; 128]), Err(Error::MessageTooLong));
+            assert_eq!(rsa.public_key.unblind(&[0xff; 128], &factor), Err(Error::InvalidSignature));
+            assert_eq!(rsa.public_key.unblind(&[0x01; 127], &factor), Err(Error::InvalidSignature));
+
+            let small = RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(85)).unwrap();
+            assert_eq!(small.public_key.blind(b"Hej", HashAlg::Sha512).err(), Some(Error::KeyTooSmall));
+        }
+
+        #[test]
+        fn blind_rejects_malformed_public_keys() {
+            for n in [0u64, 1, 2, 4, 65537] {
+                let public_key = PublicKey { n: n.into(), e: 3u64.into() };
+                assert_eq!(public_key.blind(b"Hej", HashAlg::Sha256).err(), Some(Error::KeyTooSmall), "n = {}", n);
+            }
+        }
+    }
+}
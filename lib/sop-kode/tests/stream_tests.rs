@@ -0,0 +1,200 @@
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use sop_kode::hash::HashAlg;
+use sop_kode::rsa::*;
+use sop_kode::Error;
+
+/// Writes files of `i` repeated `a`s, like `create_files` in `src/main.rs`, into `directory`.
+fn create_files(directory: &Path, start: usize, end: usize, step: usize) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
+    (start..=end)
+        .step_by(step)
+        .map(|i| {
+            let path = directory.join(format!("file_{}.txt", i));
+            File::create(&path)?.write_all("a".repeat(i).as_bytes())?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Passes reads through and counts the bytes, calling `check` with the count before every read.
+struct WatchedReader<R, F> {
+    inner: R,
+    read: u64,
+    check: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for WatchedReader<R, F> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        (self.check)(self.read);
+        let read = self.inner.read(buffer)?;
+        self.read += read as u64;
+        Ok(read)
+    }
+}
+
+/// Discards everything written to it and counts the bytes.
+struct CountingWriter(Rc<Cell<u64>>);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.0.set(self.0.get() + buffer.len() as u64);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn seeded_rsa(seed: u64) -> RSA {
+    RSA::new_with_rng(512, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod stream_tests {
+        use super::*;
+
+        #[test]
+        fn files_round_trip_through_streams() {
+            let rsa = seeded_rsa(30);
+            let directory = std::env::temp_dir().join(format!("sop_kode_stream_tests_{}", std::process::id()));
+            let files = create_files(&directory, 0, 20_000, 2_500).unwrap();
+
+            for path in files {
+                let encrypted_path = path.with_extension("sopk");
+                let decrypted_path = path.with_extension("out");
+
+                let input = BufReader::new(File::open(&path).unwrap());
+                let output = BufWriter::new(File::create(&encrypted_path).unwrap());
+                let length = rsa.public_key
+                    .encrypt_stream_with_rng(input, output, &PaddingScheme::Pkcs1v15, &mut ChaCha20Rng::seed_from_u64(36))
+                    .unwrap();
+                let encrypted = BufReader::new(File::open(&encrypted_path).unwrap());
+                assert_eq!(rsa.decrypt_stream(encrypted, File::create(&decrypted_path).unwrap()).unwrap(), length);
+
+                let plaintext = fs::read(&path).unwrap();
+                assert_eq!(fs::read(&decrypted_path).unwrap(), plaintext);
+                // Given the same padding, the stream writes the same container as encrypting in memory
+                let in_memory = rsa.public_key
+                    .encrypt_bytes_with_rng(&plaintext, &PaddingScheme::Pkcs1v15, &mut ChaCha20Rng::seed_from_u64(36))
+                    .unwrap();
+                assert_eq!(fs::read(&encrypted_path).unwrap(), in_memory.to_bytes());
+            }
+
+            fs::remove_dir_all(&directory).unwrap();
+        }
+
+        #[test]
+        fn lengths_around_chunk_boundaries_round_trip() {
+            let rsa = seeded_rsa(31);
+            let chunk_size = rsa.public_key.chunk_size(&PaddingScheme::Pkcs1v15).unwrap();
+
+            for length in [0, 1, chunk_size - 9, chunk_size - 8, chunk_size - 7, chunk_size, chunk_size + 1, 3 * chunk_size] {
+                let message: Vec<u8> = (0..length).map(|i| (i * 7) as u8).collect();
+                let mut encrypted = Vec::new();
+                rsa.public_key.encrypt_stream(&message[..], &mut encrypted).unwrap();
+
+                let mut decrypted = Vec::new();
+                rsa.decrypt_stream(&encrypted[..], &mut decrypted).unwrap();
+                assert_eq!(decrypted, message, "length {}", length);
+                assert_eq!(rsa.decrypt_bytes(&Ciphertext::from_bytes(&encrypted).unwrap()).unwrap(), message);
+            }
+        }
+
+        #[test]
+        fn output_keeps_up_with_input() {
+            let rsa = seeded_rsa(32);
+            let chunk_size = rsa.public_key.chunk_size(&PaddingScheme::Pkcs1v15).unwrap() as u64;
+            let block_size = rsa.public_key.byte_len() as u64;
+            let length = 1 << 18;
+
+            // Encrypting never reads more than one chunk ahead of the blocks already written
+            let written = Rc::new(Cell::new(0u64));
+            let watched = Rc::clone(&written);
+            let reader = WatchedReader {
+                inner: io::repeat(b'a').take(length),
+                read: 0,
+                check: move |read: u64| {
+                    let blocks = watched.get().saturating_sub(42) / block_size;
+                    assert!(read <= (blocks + 1) * chunk_size, "read {} bytes but wrote {} blocks", read, blocks);
+                },
+            };
+            assert_eq!(rsa.public_key.encrypt_stream(reader, CountingWriter(Rc::clone(&written))).unwrap(), length);
+            let blocks = (length + 8).div_ceil(chunk_size);
+            assert_eq!(written.get(), 42 + blocks * block_size + 16);
+
+            // Decrypting writes everything but the last chunk and suffix as soon as it is decrypted
+            let mut encrypted = Vec::new();
+            rsa.public_key.encrypt_stream(io::repeat(b'a').take(length), &mut encrypted).unwrap();
+            let decrypted = Rc::new(Cell::new(0u64));
+            let watched = Rc::clone(&decrypted);
+            let reader = WatchedReader {
+                inner: &encrypted[..],
+                read: 0,
+                check: move |read: u64| {
+                    let blocks = read.saturating_sub(42) / block_size;
+                    assert!(watched.get() + 3 * chunk_size >= blocks * chunk_size, "read {} blocks but wrote {} bytes", blocks, watched.get());
+                },
+            };
+            assert_eq!(rsa.decrypt_stream(reader, CountingWriter(Rc::clone(&decrypted))).unwrap(), length);
+            assert_eq!(decrypted.get(), length);
+        }
+
+        #[test]
+        fn rejects_truncated_and_foreign_streams() {
+            let rsa = seeded_rsa(33);
+            let mut encrypted = Vec::new();
+            rsa.public_key.encrypt_stream(&[1u8; 200][..], &mut encrypted).unwrap();
+
+            for length in [0, 10, 42, 42 + 30, 42 + 64, encrypted.len() - 16, encrypted.len() - 1] {
+                let result = rsa.decrypt_stream(&encrypted[..length], io::sink());
+                assert!(matches!(result, Err(Error::InvalidCiphertext(_))), "length {}: {:?}", length, result);
+            }
+            assert_eq!(seeded_rsa(34).decrypt_stream(&encrypted[..], io::sink()).err(), Some(Error::WrongKey));
+
+            let mut tampered = encrypted.clone();
+            *tampered.last_mut().unwrap() ^= 1;
+            assert_eq!(rsa.decrypt_stream(&tampered[..], io::sink()).err(), Some(Error::InvalidFraming));
+        }
+
+        #[test]
+        fn labelled_streams_need_their_label() {
+            let rsa = seeded_rsa(37);
+            let padding = PaddingScheme::oaep(HashAlg::Sha1).with_label(b"fil");
+            let mut encrypted = Vec::new();
+            rsa.public_key
+                .encrypt_stream_with_rng(&[7u8; 300][..], &mut encrypted, &padding, &mut ChaCha20Rng::seed_from_u64(38))
+                .unwrap();
+
+            let mut decrypted = Vec::new();
+            assert_eq!(rsa.decrypt_stream_with_label(&encrypted[..], &mut decrypted, b"fil").unwrap(), 300);
+            assert_eq!(decrypted, [7u8; 300]);
+            assert_eq!(rsa.decrypt_stream(&encrypted[..], io::sink()).err(), Some(Error::LabelMismatch));
+        }
+
+        #[test]
+        fn reports_io_errors() {
+            struct FailingReader;
+            impl Read for FailingReader {
+                fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                    Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+                }
+            }
+
+            let rsa = seeded_rsa(35);
+            assert_eq!(rsa.public_key.encrypt_stream(FailingReader, io::sink()).err(), Some(Error::Io(io::ErrorKind::BrokenPipe)));
+            assert_eq!(rsa.decrypt_stream(FailingReader, io::sink()).err(), Some(Error::Io(io::ErrorKind::BrokenPipe)));
+        }
+    }
+}